@@ -1,6 +1,4 @@
-use std::rc::Rc;
-use std::cell::RefCell;
-
+use crate::common::Span;
 use crate::common::serialize::*;
 
 use super::AstIndex;
@@ -15,18 +13,21 @@ pub trait Pushable<T> {
     fn push(&mut self, _: T) -> AstIndex;
 }
 
-pub struct Ast {
-    // Arena-allocated tree: uses a vector
-    tree: Vec<Rc<RefCell<AstElement>>>,
+pub struct Ast<'a> {
+    // Arena-allocated tree: uses a vector. The elements are plain values, so a compiled Ast can be shared between threads
+    tree: Vec<AstElement<'a>>,
+    // Source range of every element of the tree, by index 
+    spans: Vec<Span>,
     // The head of the Ast is not necessarily the start of the vector
     // Depends on how the parser allocated elements in the tree. (Probably will be built bottom up per block)
     head: Option<AstIndex>,
 }
 
-impl Ast {
-    pub fn new() -> Ast {
+impl<'a> Ast<'a> {
+    pub fn new() -> Ast<'a> {
         Ast {
             tree: vec![],
+            spans: vec![],
             head: None,
         }
     }
@@ -42,12 +43,33 @@ impl Ast {
         }
     }
 
-    pub fn get(&self, index: AstIndex) -> Rc<RefCell<AstElement>> {
-        Rc::clone(self.tree.get(index.0).unwrap())
+    pub fn get(&self, index: AstIndex) -> &AstElement<'a> {
+        &self.tree[index.0]
+    }
+
+    /// Only the parser and the semantic analysis modify the elements, while they build the tree
+    pub fn get_mut(&mut self, index: AstIndex) -> &mut AstElement<'a> {
+        &mut self.tree[index.0]
+    }
+
+    /// The source range of the element: from its first token to its last token
+    pub fn span(&self, index: AstIndex) -> Span {
+        self.spans[index.0]
+    }
+
+    pub fn set_span(&mut self, index: AstIndex, span: Span) {
+        self.spans[index.0] = span;
+    }
+
+    fn push_element(&mut self, element: impl FnOnce(AstIndex) -> AstElement<'a>) -> AstIndex {
+        let insertion_index = AstIndex(self.tree.len());
+        self.tree.push(element(insertion_index));
+        self.spans.push(Span::default());
+        insertion_index
     }
 }
 
-impl Serializable for Ast {
+impl Serializable for Ast<'_> {
     fn serialize(&self, serde: &mut Serializer, ctx: &dyn IndexSerializable) -> Option<AstIndex> {
         let _ast = serde.open_tag("Ast");
 
@@ -55,7 +77,7 @@ impl Serializable for Ast {
         //   serialize(), returns index to next item to serialize, serialize the next element until we get None
         if let Some(mut current) = self.head {
             loop { 
-                let next = self.get(current).borrow().serialize(serde, ctx);
+                let next = self.get(current).serialize(serde, ctx);
                 match next {
                     Some(next) => current = next,
                     None => break,
@@ -67,70 +89,55 @@ impl Serializable for Ast {
     }
 }
 
-impl Indexable for Ast {
-    fn get(&self, index: &AstIndex) -> Rc<RefCell<AstElement>> {
+impl Indexable for Ast<'_> {
+    fn get(&self, index: &AstIndex) -> &AstElement<'_> {
         self.get(*index)
     }
 }
 
-impl IndexSerializable for Ast {}
+impl IndexSerializable for Ast<'_> {}
 
 // All types that can be pushed onto the AST
-impl Pushable<Block> for Ast {
-    fn push(&mut self, block: Block) -> AstIndex {
-        let insertion_index = AstIndex(self.tree.len());
-        let element = AstElement::Block(insertion_index, block);
-        self.tree.push(Rc::new(RefCell::new(element)));
-        insertion_index
+impl<'a> Pushable<Block<'a>> for Ast<'a> {
+    fn push(&mut self, block: Block<'a>) -> AstIndex {
+        self.push_element(|index| AstElement::Block(index, block))
     }
 }
 
-impl Pushable<Statement> for Ast {
-    fn push(&mut self, statement: Statement) -> AstIndex {
-        let insertion_index = AstIndex(self.tree.len());
-        let element = AstElement::Statement(insertion_index, statement);
-        self.tree.push(Rc::new(RefCell::new(element)));
-        insertion_index
+impl<'a> Pushable<Statement<'a>> for Ast<'a> {
+    fn push(&mut self, statement: Statement<'a>) -> AstIndex {
+        self.push_element(|index| AstElement::Statement(index, statement))
     }
 }
 
-impl Pushable<Expression> for Ast {
-    fn push(&mut self, expression: Expression) -> AstIndex {
-        let insertion_index = AstIndex(self.tree.len());
-        let element = AstElement::Expression(insertion_index, expression);
-        self.tree.push(Rc::new(RefCell::new(element)));
-        insertion_index
+impl<'a> Pushable<Expression<'a>> for Ast<'a> {
+    fn push(&mut self, expression: Expression<'a>) -> AstIndex {
+        self.push_element(|index| AstElement::Expression(index, expression))
     }
 }
 
-impl Pushable<Transform> for Ast {
-    fn push(&mut self, transform: Transform) -> AstIndex {
-        let insertion_index = AstIndex(self.tree.len());
-        let element = AstElement::Transform(insertion_index, transform);
-        self.tree.push(Rc::new(RefCell::new(element)));
-        insertion_index
+impl<'a> Pushable<Transform<'a>> for Ast<'a> {
+    fn push(&mut self, transform: Transform<'a>) -> AstIndex {
+        self.push_element(|index| AstElement::Transform(index, transform))
     }
 }
 
-impl Pushable<Pattern> for Ast {
-    fn push(&mut self, pattern: Pattern) -> AstIndex {
-        let insertion_index = AstIndex(self.tree.len());
-        let element = AstElement::Pattern(insertion_index, pattern);
-        self.tree.push(Rc::new(RefCell::new(element)));
-        insertion_index
+impl<'a> Pushable<Pattern<'a>> for Ast<'a> {
+    fn push(&mut self, pattern: Pattern<'a>) -> AstIndex {
+        self.push_element(|index| AstElement::Pattern(index, pattern))
     }
 }
 
-pub enum AstElement {
+pub enum AstElement<'a> {
     // First item of every AstElement is the index that points to itself 
-    Block(AstIndex, Block),
-    Statement(AstIndex, Statement),
-    Expression(AstIndex, Expression),
-    Transform(AstIndex, Transform),
-    Pattern(AstIndex, Pattern),
+    Block(AstIndex, Block<'a>),
+    Statement(AstIndex, Statement<'a>),
+    Expression(AstIndex, Expression<'a>),
+    Transform(AstIndex, Transform<'a>),
+    Pattern(AstIndex, Pattern<'a>),
 }
 
-impl AstElement {
+impl<'a> AstElement<'a> {
     pub fn index(&self) -> &AstIndex {
         match self {
             AstElement::Block(index, _) => index,
@@ -142,7 +149,7 @@ impl AstElement {
     }
 }
 
-impl Serializable for AstElement {
+impl Serializable for AstElement<'_> {
     fn serialize(&self, serde: &mut Serializer, ctx: &dyn IndexSerializable) -> Option<AstIndex> {
         match self {
             AstElement::Block(_, block) => block.serialize(serde, ctx),
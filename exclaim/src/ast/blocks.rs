@@ -10,9 +10,9 @@ type StatementIndex = AstIndex;
 type Scope = Vec<AstIndex>;
 
 #[derive(Debug)]
-pub enum Block {
+pub enum Block<'a> {
     /// Text(text: Token, next_block: Option<AstIndex>)
-    Text(Token, Option<BlockIndex>),
+    Text(Token<'a>, Option<BlockIndex>),
     /// CodeEnclosed(stmt: AstIndex, next_block: Option<AstIndex>)
     CodeEnclosed(StatementIndex, Option<BlockIndex>),
     /// CodeUnclosed(stmt: AstIndex, scope: Vec<AstIndex>, next_block: Option<AstIndex>)
@@ -21,8 +21,8 @@ pub enum Block {
     CodeClosing(StatementIndex, Option<BlockIndex>),
 }
 
-impl Block {
-    pub fn text(&self) -> Option<&Token> {
+impl<'a> Block<'a> {
+    pub fn text(&self) -> Option<&Token<'a>> {
         match self {
             Block::Text(text, _) => Some(text),
             _ => None,
@@ -66,7 +66,7 @@ impl Block {
     }
 }
 
-impl Serializable for Block {
+impl Serializable for Block<'_> {
     fn serialize(&self, serde: &mut Serializer, ctx: &dyn IndexSerializable) -> Option<AstIndex> {
         match self {
             Block::Text(text, next) => {
@@ -3,15 +3,38 @@ use crate::common::serialize::*;
 use crate::tokens::Token;
 
 type TransformIndex = AstIndex;
+type ExpressionIndex = AstIndex;
 
-pub enum Expression {
+pub enum Expression<'a> {
     /// Literal(literal: Token)
-    Literal(Token, Vec<TransformIndex>),
-    /// Reference(list: Vec<Token>)
-    Reference(Vec<Token>, Vec<TransformIndex>)
+    Literal(Token<'a>, Vec<TransformIndex>),
+    /// Reference(accessors: Vec<Access<'a>>) 
+    /// 
+    /// The first accessor is the member with the label of the variable
+    Reference(Vec<Access<'a>>, Vec<TransformIndex>),
+    /// Access(operand: ExpressionIndex, accessors: Vec<Access<'a>>) 
+    /// 
+    /// Members and elements of a parenthesized expression or a collection literal
+    Access(ExpressionIndex, Vec<Access<'a>>, Vec<TransformIndex>),
+    /// Group(expression: ExpressionIndex) 
+    /// 
+    /// An expression inside of parentheses
+    Group(ExpressionIndex, Vec<TransformIndex>),
+    /// Array(elements: Vec<ExpressionIndex>)
+    Array(Vec<ExpressionIndex>, Vec<TransformIndex>),
+    /// Tuple(elements: Vec<ExpressionIndex>)
+    Tuple(Vec<ExpressionIndex>, Vec<TransformIndex>),
+    /// Object(members: Vec<(key: Token, value: ExpressionIndex)>)
+    /// 
+    /// The key is a label or a string literal
+    Object(Vec<(Token<'a>, ExpressionIndex)>, Vec<TransformIndex>),
+    /// Unary(operator: Token, operand: ExpressionIndex)
+    Unary(Token<'a>, ExpressionIndex),
+    /// Binary(operator: Token, lhs: ExpressionIndex, rhs: ExpressionIndex)
+    Binary(Token<'a>, ExpressionIndex, ExpressionIndex),
 }
 
-impl Serializable for Expression {
+impl Serializable for Expression<'_> {
     fn serialize(&self, serde: &mut Serializer, ctx: &dyn IndexSerializable) -> Option<AstIndex> {
         match self {
             Expression::Literal(literal, transforms) => {
@@ -28,6 +51,89 @@ impl Serializable for Expression {
                 let _tranforms = serde.open_tag("Transforms");
                 transforms.serialize(serde, ctx)
             }
+            Expression::Access(operand, accessors, transforms) => {
+                let _expression = serde.open_tag("AccessExpression");
+                operand.serialize(serde, ctx);
+
+                let accessors_tag = serde.open_tag("Accessors");
+                accessors.serialize(serde, ctx);
+                serde.close_tag(accessors_tag);
+
+                let _tranforms = serde.open_tag("Transforms");
+                transforms.serialize(serde, ctx)
+            }
+            Expression::Group(expression, transforms) => {
+                let _expression = serde.open_tag("GroupExpression");
+                expression.serialize(serde, ctx);
+
+                let _tranforms = serde.open_tag("Transforms");
+                transforms.serialize(serde, ctx)
+            }
+            Expression::Array(elements, transforms) => {
+                let _expression = serde.open_tag("ArrayExpression");
+                let elements_tag = serde.open_tag("Elements");
+                elements.serialize(serde, ctx);
+                serde.close_tag(elements_tag);
+
+                let _tranforms = serde.open_tag("Transforms");
+                transforms.serialize(serde, ctx)
+            }
+            Expression::Tuple(elements, transforms) => {
+                let _expression = serde.open_tag("TupleExpression");
+                let elements_tag = serde.open_tag("Elements");
+                elements.serialize(serde, ctx);
+                serde.close_tag(elements_tag);
+
+                let _tranforms = serde.open_tag("Transforms");
+                transforms.serialize(serde, ctx)
+            }
+            Expression::Object(members, transforms) => {
+                let _expression = serde.open_tag("ObjectExpression");
+                let members_tag = serde.open_tag("Members");
+                for (key, value) in members {
+                    let _member = serde.open_tag("Member");
+                    key.serialize(serde, ctx);
+                    value.serialize(serde, ctx);
+                }
+                serde.close_tag(members_tag);
+
+                let _tranforms = serde.open_tag("Transforms");
+                transforms.serialize(serde, ctx)
+            }
+            Expression::Unary(operator, operand) => {
+                let _expression = serde.open_tag("UnaryExpression");
+                operator.serialize(serde, ctx);
+                operand.serialize(serde, ctx)
+            }
+            Expression::Binary(operator, lhs, rhs) => {
+                let _expression = serde.open_tag("BinaryExpression");
+                operator.serialize(serde, ctx);
+                lhs.serialize(serde, ctx);
+                rhs.serialize(serde, ctx)
+            }
+        }
+    }
+}
+
+pub enum Access<'a> {
+    /// Member(label: Token) 
+    /// 
+    /// .label
+    Member(Token<'a>),
+    /// Index(index: ExpressionIndex) 
+    /// 
+    /// [index], an integer index of an array or tuple, or a string key of an object
+    Index(ExpressionIndex),
+}
+
+impl Serializable for Access<'_> {
+    fn serialize(&self, serde: &mut Serializer, ctx: &dyn IndexSerializable) -> Option<AstIndex> {
+        match self {
+            Access::Member(label) => label.serialize(serde, ctx),
+            Access::Index(index) => {
+                let _index = serde.open_tag("Index");
+                index.serialize(serde, ctx)
+            }
         }
     }
-}
\ No newline at end of file
+}
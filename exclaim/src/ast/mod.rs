@@ -15,10 +15,11 @@ impl Deref for AstIndex {
 
 impl Serializable for AstIndex {
     fn serialize(&self, serde: &mut Serializer, ctx: &dyn IndexSerializable) -> Option<AstIndex> {
-        ctx.get(self).borrow().serialize(serde, ctx)
+        ctx.get(self).serialize(serde, ctx)
     }
 }
 
+#[allow(clippy::module_inception)]
 pub mod ast;
 pub mod blocks;
 pub mod statements;
@@ -34,6 +35,7 @@ pub mod prelude {
     pub use super::blocks::Block;
     pub use super::statements::Statement;
     pub use super::expressions::Expression;
+    pub use super::expressions::Access;
     pub use super::transforms::Transform;
     pub use super::patterns::Pattern;
 }
\ No newline at end of file
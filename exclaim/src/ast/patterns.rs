@@ -1,11 +1,11 @@
 use crate::common::serialize::*;
 use crate::tokens::Token;
-pub enum Pattern {
+pub enum Pattern<'a> {
     // Decleration(decls: Vec<Token>)
-    Decleration(Vec<Token>)
+    Decleration(Vec<Token<'a>>)
 }
 
-impl Serializable for Pattern {
+impl Serializable for Pattern<'_> {
     fn serialize(&self, serde: &mut Serializer, ctx: &dyn IndexSerializable) -> Option<super::AstIndex> {
         match self {
             Pattern::Decleration(decls) => {
@@ -6,20 +6,56 @@ use super::AstIndex;
 type ExpressionIndex = AstIndex;
 type PatternIndex = AstIndex;
 
-pub enum Statement {
+pub enum Statement<'a> {
     /// End statement: {{!}}
     /// 
     /// End(action: Token, )
-    End(Token),
+    End(Token<'a>),
     /// Let(action: Token, pattern: AstIndex, expression: AstIndex)
-    Let(Token, PatternIndex, ExpressionIndex),
+    Let(Token<'a>, PatternIndex, ExpressionIndex),
     /// Render(action: Token, pattern: AstIndex, expression: AstIndex)
-    Render(Token, PatternIndex, ExpressionIndex),
+    Render(Token<'a>, PatternIndex, ExpressionIndex),
     /// Write(action: Token, expression: AstIndex)
-    Write(Token, ExpressionIndex),
+    Write(Token<'a>, ExpressionIndex),
+    /// Include(action: Token, template_name: AstIndex)
+    Include(Token<'a>, ExpressionIndex),
+    /// Extends(action: Token, template_name: AstIndex)
+    Extends(Token<'a>, ExpressionIndex),
+    /// Block(action: Token, name: Token)
+    Block(Token<'a>, Token<'a>),
+    /// Super statement: {{ super! }}
+    /// 
+    /// Super(action: Token)
+    Super(Token<'a>),
+    /// If(action: Token, condition: AstIndex)
+    If(Token<'a>, ExpressionIndex),
+    /// Elif(action: Token, condition: AstIndex)
+    Elif(Token<'a>, ExpressionIndex),
+    /// Else statement: {{ else! }}
+    /// 
+    /// Else(action: Token)
+    Else(Token<'a>),
 }
 
-impl Serializable for Statement {
+impl<'a> Statement<'a> {
+    pub fn action(&self) -> &Token<'a> {
+        match self {
+            Statement::End(action) => action,
+            Statement::Let(action, _, _) => action,
+            Statement::Render(action, _, _) => action,
+            Statement::Write(action, _) => action,
+            Statement::Include(action, _) => action,
+            Statement::Extends(action, _) => action,
+            Statement::Block(action, _) => action,
+            Statement::Super(action) => action,
+            Statement::If(action, _) => action,
+            Statement::Elif(action, _) => action,
+            Statement::Else(action) => action,
+        }
+    }
+}
+
+impl Serializable for Statement<'_> {
     fn serialize(&self, serde: &mut Serializer, ctx: &dyn IndexSerializable) -> Option<AstIndex> {
         match self {
             Statement::End(action) => {
@@ -43,6 +79,39 @@ impl Serializable for Statement {
                 action.serialize(serde, ctx);
                 expression.serialize(serde, ctx)
             }
+            Statement::Include(action, template_name) => {
+                let _statement = serde.open_tag("IncludeStatement");
+                action.serialize(serde, ctx);
+                template_name.serialize(serde, ctx)
+            }
+            Statement::Extends(action, template_name) => {
+                let _statement = serde.open_tag("ExtendsStatement");
+                action.serialize(serde, ctx);
+                template_name.serialize(serde, ctx)
+            }
+            Statement::Block(action, name) => {
+                let _statement = serde.open_tag("BlockStatement");
+                action.serialize(serde, ctx);
+                name.serialize(serde, ctx)
+            }
+            Statement::Super(action) => {
+                let _statement = serde.open_tag("SuperStatement");
+                action.serialize(serde, ctx)
+            }
+            Statement::If(action, condition) => {
+                let _statement = serde.open_tag("IfStatement");
+                action.serialize(serde, ctx);
+                condition.serialize(serde, ctx)
+            }
+            Statement::Elif(action, condition) => {
+                let _statement = serde.open_tag("ElifStatement");
+                action.serialize(serde, ctx);
+                condition.serialize(serde, ctx)
+            }
+            Statement::Else(action) => {
+                let _statement = serde.open_tag("ElseStatement");
+                action.serialize(serde, ctx)
+            }
         }
     }
 }
@@ -1,4 +1,5 @@
 use crate::tokens::Token;
+use crate::common::Location;
 use crate::common::serialize::*;
 
 use super::AstIndex;
@@ -7,10 +8,10 @@ type ExpressionIndex = AstIndex;
 
 // Transform(label: Token, args: Vec<AstIndex>)
 #[derive(Debug)]
-pub struct Transform(Token, Vec<ExpressionIndex>);
+pub struct Transform<'a>(Token<'a>, Vec<ExpressionIndex>);
 
-impl Transform {
-    pub fn new(label: Token, arguments: Vec<ExpressionIndex>) -> Transform {
+impl<'a> Transform<'a> {
+    pub fn new(label: Token<'a>, arguments: Vec<ExpressionIndex>) -> Transform<'a> {
         Transform(label, arguments)
     }
 
@@ -21,6 +22,10 @@ impl Transform {
         }
     }
 
+    pub fn location(&self) -> Location {
+        self.0.location()
+    }
+
     pub fn arguments(&self) -> &Vec<ExpressionIndex> {
         &self.1
     }
@@ -34,11 +39,11 @@ impl Transform {
     }
 
     pub fn has_arguments(&self) -> bool {
-        self.1.len() != 0
+        !self.1.is_empty()
     }
 }
 
-impl Serializable for Transform {
+impl Serializable for Transform<'_> {
     fn serialize(&self, serde: &mut Serializer, ctx: &dyn IndexSerializable) -> Option<AstIndex> {
         let _transform =  serde.open_tag("Transform");
         self.0.serialize(serde, ctx);
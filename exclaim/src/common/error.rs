@@ -0,0 +1,103 @@
+use std::error;
+use std::fmt;
+
+use super::Location;
+
+/// The error type returned by every stage of the template pipeline.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ExclaimError {
+    /// Lex(location: Location, message: String)
+    Lex(Location, String),
+    /// Parse(location: Location, message: String)
+    Parse(Location, String),
+    /// Semantic(location: Location, message: String)
+    Semantic(Location, String),
+    /// Runtime(location: Location, message: String)
+    Runtime(Location, String),
+    /// Transform(location: Location, message: String)
+    Transform(Location, String),
+    /// Multiple(errors: Vec<ExclaimError>)
+    /// 
+    /// Every error reported by a stage that recovers from errors, in the order they were found. 
+    /// Holds at least two errors.
+    Multiple(Vec<ExclaimError>),
+}
+
+impl ExclaimError {
+    pub fn location(&self) -> Location {
+        match self {
+            ExclaimError::Lex(location, _) => *location,
+            ExclaimError::Parse(location, _) => *location,
+            ExclaimError::Semantic(location, _) => *location,
+            ExclaimError::Runtime(location, _) => *location,
+            ExclaimError::Transform(location, _) => *location,
+            ExclaimError::Multiple(errors) => errors.first().map(|e| e.location()).unwrap_or_default(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ExclaimError::Lex(_, message) => message,
+            ExclaimError::Parse(_, message) => message,
+            ExclaimError::Semantic(_, message) => message,
+            ExclaimError::Runtime(_, message) => message,
+            ExclaimError::Transform(_, message) => message,
+            ExclaimError::Multiple(errors) => errors.first().map(|e| e.message()).unwrap_or_default(),
+        }
+    }
+
+    /// Every error held by this error. Only ExclaimError::Multiple holds more than one.
+    pub fn errors(&self) -> &[ExclaimError] {
+        match self {
+            ExclaimError::Multiple(errors) => errors,
+            _ => std::slice::from_ref(self),
+        }
+    }
+
+    /// Returns the same kind of error at the same location with the message transformed by f
+    pub fn map_message<F: Fn(String) -> String>(self, f: F) -> ExclaimError {
+        self.map_message_with(&f)
+    }
+
+    fn map_message_with(self, f: &dyn Fn(String) -> String) -> ExclaimError {
+        match self {
+            ExclaimError::Lex(location, message) => ExclaimError::Lex(location, f(message)),
+            ExclaimError::Parse(location, message) => ExclaimError::Parse(location, f(message)),
+            ExclaimError::Semantic(location, message) => ExclaimError::Semantic(location, f(message)),
+            ExclaimError::Runtime(location, message) => ExclaimError::Runtime(location, f(message)),
+            ExclaimError::Transform(location, message) => ExclaimError::Transform(location, f(message)),
+            ExclaimError::Multiple(errors) => ExclaimError::Multiple(errors.into_iter().map(|e| e.map_message_with(f)).collect()),
+        }
+    }
+
+    /// Name of the stage that produced the error
+    pub fn stage(&self) -> &'static str {
+        match self {
+            ExclaimError::Lex(_, _) => "Lexer",
+            ExclaimError::Parse(_, _) => "Parser",
+            ExclaimError::Semantic(_, _) => "Semantic",
+            ExclaimError::Runtime(_, _) => "Runtime",
+            ExclaimError::Transform(_, _) => "Transform",
+            ExclaimError::Multiple(errors) => errors.first().map(|e| e.stage()).unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Display for ExclaimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let ExclaimError::Multiple(errors) = self {
+            for (i, error) in errors.iter().enumerate() {
+                if i > 0 {
+                    writeln!(f)?;
+                }
+                write!(f, "{}", error)?;
+            }
+            return Ok(());
+        }
+
+        let location = self.location();
+        write!(f, "{} error [{}; {}]: {}", self.stage(), location.line(), location.column(), self.message())
+    }
+}
+
+impl error::Error for ExclaimError {}
@@ -3,17 +3,31 @@ use std::convert;
 use crate::ast::AstIndex;
 use crate::common::serialize::*;
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+/// Columns between two tab stops in source snippets
+const TAB_WIDTH: usize = 4;
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
 pub struct Location {
     line: usize,
     column: usize, 
+    // Byte offset in the source
+    offset: usize,
 }
 
 impl Location {
     pub fn new(line: usize, column: usize) -> Location {
         Location {
             line,
-            column
+            column,
+            offset: 0,
+        }
+    }
+
+    pub fn with_offset(line: usize, column: usize, offset: usize) -> Location {
+        Location {
+            line,
+            column,
+            offset,
         }
     }
 
@@ -22,8 +36,10 @@ impl Location {
         self.column = 0;
     } 
 
-    pub fn shift(&mut self) {
+    /// Moves the location past ch
+    pub fn shift(&mut self, ch: char) {
         self.column += 1;
+        self.offset += ch.len_utf8();
     }
 
     pub fn line(&self) -> usize {
@@ -33,14 +49,77 @@ impl Location {
     pub fn column(&self) -> usize {
         self.column
     }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Renders the line of the source at this location, with a caret and the underline message under the column. 
+    /// Matches the snippets of the lexer errors.
+    /// Tabs are expanded to spaces and wide characters take two columns, so the caret lines up under the column in a terminal.
+    pub fn debug_line(&self, source: &str, underline_msg: &str) -> String {
+        let source = source.strip_prefix('\u{FEFF}').unwrap_or(source);
+        let mut line = String::new();
+        let mut caret = None;
+        let mut width = 0;
+        let mut columns = 0;
+        for ch in source.lines().nth(self.line).unwrap_or_default().chars() {
+            if columns == self.column {
+                caret = Some(width);
+            }
+            columns += 1;
+
+            if ch == '\t' {
+                let spaces = TAB_WIDTH - width % TAB_WIDTH;
+                line.push_str(&" ".repeat(spaces));
+                width += spaces;
+            } else {
+                line.push(ch);
+                width += display_width(ch);
+            }
+        }
+        // The column can be past the end of the line, e.g. at a line ending
+        let caret = caret.unwrap_or(width + self.column.saturating_sub(columns));
+
+        // Underline location of error 
+        line.push('\n');
+        line.push('\t');
+        line.push_str(&" ".repeat(caret));
+        line.push('^');
+        line.push(' ');
+        line.push_str(underline_msg);
+
+        line
+    }
+}
+
+/// Number of columns a character takes in a terminal: zero for control and combining characters, two for wide East Asian characters and emoji
+fn display_width(ch: char) -> usize {
+    match ch {
+        _ if ch.is_control() => 0,
+        '\u{0300}'..='\u{036F}' | '\u{200B}'..='\u{200F}' | '\u{FE00}'..='\u{FE0F}' | '\u{FEFF}' => 0,
+        '\u{1100}'..='\u{115F}' 
+        | '\u{2E80}'..='\u{303E}' 
+        | '\u{3041}'..='\u{33FF}' 
+        | '\u{3400}'..='\u{4DBF}' 
+        | '\u{4E00}'..='\u{9FFF}' 
+        | '\u{A000}'..='\u{A4CF}' 
+        | '\u{AC00}'..='\u{D7A3}' 
+        | '\u{F900}'..='\u{FAFF}' 
+        | '\u{FE30}'..='\u{FE4F}' 
+        | '\u{FF00}'..='\u{FF60}' 
+        | '\u{FFE0}'..='\u{FFE6}' 
+        | '\u{1F300}'..='\u{1F64F}' 
+        | '\u{1F900}'..='\u{1F9FF}' 
+        | '\u{20000}'..='\u{2FFFD}' 
+        | '\u{30000}'..='\u{3FFFD}' => 2,
+        _ => 1,
+    }
 }
 
 impl convert::From<(usize, usize)> for Location {
     fn from((line, column): (usize, usize)) -> Location {
-        Location {
-            line,
-            column
-        }
+        Location::new(line, column)
     }
 }
 
@@ -1,6 +1,12 @@
 pub mod location;
 pub use location::Location;
 
+pub mod span;
+pub use span::Span;
+
 pub mod serialize;
 pub use serialize::Serializer;
-pub use serialize::Serializable;
\ No newline at end of file
+pub use serialize::Serializable;
+
+pub mod error;
+pub use error::ExclaimError;
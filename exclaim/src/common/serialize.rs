@@ -7,7 +7,7 @@ use crate::ast::prelude::{
 };
 
 pub trait Indexable {
-    fn get(&self, index: &AstIndex) -> Rc<RefCell<AstElement>>;
+    fn get(&self, index: &AstIndex) -> &AstElement<'_>;
 }
 
 pub trait Serializable {
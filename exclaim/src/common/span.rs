@@ -0,0 +1,39 @@
+use std::ops::Range;
+
+use crate::common::Location;
+
+/// Span(start: Location, end: Location)
+/// 
+/// The source range of a token or an ast node. end is the location right after the last character.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Span {
+    start: Location,
+    end: Location,
+}
+
+impl Span {
+    pub fn new(start: Location, end: Location) -> Span {
+        Span {
+            start,
+            end,
+        }
+    }
+
+    pub fn start(&self) -> Location {
+        self.start
+    }
+
+    pub fn end(&self) -> Location {
+        self.end
+    }
+
+    /// Span from the start of this span to the end of the other span
+    pub fn to(&self, other: Span) -> Span {
+        Span::new(self.start, other.end)
+    }
+
+    /// Byte range of the span in the source
+    pub fn range(&self) -> Range<usize> {
+        self.start.offset()..self.end.offset()
+    }
+}
@@ -2,9 +2,12 @@ use std::collections::{
     BTreeMap,
     HashMap,
 };
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt::Debug;
 
 use crate::ast::transforms::Transform;
+use crate::common::ExclaimError;
 use crate::tokens::{ 
     Token,
     Number,
@@ -16,6 +19,12 @@ use traits::Renderable;
 pub mod transforms;
 use transforms::apply_transform;
 
+pub mod operators;
+use operators::{
+    apply_operator,
+    apply_unary_operator,
+};
+
 #[derive(Clone)]
 pub enum Data {
     // Scalar
@@ -23,6 +32,8 @@ pub enum Data {
     Int(isize),
     Uint(usize),
     Float(f64),
+    Bool(bool),
+    Null,
 
     // Compound Types
     Array(Vec<Data>),
@@ -33,10 +44,13 @@ pub enum Data {
     Option(Option<Box<Data>>),
 }
 
-impl From<Token> for Data {
-    fn from(token: Token) -> Self {
-        match token {
-            Token::StringLiteral(string, _) => Data::String(string),
+/// Only literals hold a value, any other token is an error at its location
+impl TryFrom<Token<'_>> for Data {
+    type Error = ExclaimError;
+
+    fn try_from(token: Token<'_>) -> Result<Self, Self::Error> {
+        let data = match token {
+            Token::StringLiteral(string, _) => Data::String(string.into_owned()),
             Token::NumberLiteral(number, _) => {
                 match number {
                     Number::Uint(uint) => Data::Uint(uint),
@@ -44,23 +58,72 @@ impl From<Token> for Data {
                     Number::Float(float) => Data::Float(float),
                 }
             },
-            _ => panic!("Cannot convert token into Data: {:?}", token),
-        }
+            Token::BoolLiteral(boolean, _) => Data::Bool(boolean),
+            Token::NullLiteral(_) => Data::Null,
+            _ => return Err(ExclaimError::Runtime(token.location(), format!("Expected a literal value, but found '{}'.", token))),
+        };
+
+        Ok(data)
     }
 }
 
 impl Data {
-    pub fn apply_transform(self, transform: &Transform, arguments: Vec<Data>) -> Data {
+    pub fn apply_transform(self, transform: &Transform, arguments: Vec<Data>) -> Result<Data, ExclaimError> {
         apply_transform(self, transform, arguments)
     }
 
+    pub fn apply_operator(self, operator: &Token, rhs: Data) -> Result<Data, ExclaimError> {
+        apply_operator(self, operator, rhs)
+    }
+
+    pub fn apply_unary_operator(self, operator: &Token) -> Result<Data, ExclaimError> {
+        apply_unary_operator(operator, self)
+    }
+
     pub fn is_scalar(&self) -> bool {
+        matches!(self, Data::String(_) | Data::Int(_) | Data::Uint(_) | Data::Float(_) | Data::Bool(_) | Data::Null)
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, Data::Int(_) | Data::Uint(_) | Data::Float(_))
+    }
+
+    /// Order of the data for the comparison operators. 
+    /// Numbers are ordered by value across Int, Uint and Float, strings are ordered lexicographically, anything else is unordered. 
+    pub fn compare(&self, other: &Data) -> Option<Ordering> {
+        match (self, other) {
+            (Data::String(lhs), Data::String(rhs)) => Some(lhs.cmp(rhs)),
+            (Data::Int(lhs), Data::Int(rhs)) => Some(lhs.cmp(rhs)),
+            (Data::Uint(lhs), Data::Uint(rhs)) => Some(lhs.cmp(rhs)),
+            (Data::Float(lhs), Data::Float(rhs)) => lhs.partial_cmp(rhs),
+            (Data::Int(lhs), Data::Uint(rhs)) => Some((*lhs as i128).cmp(&(*rhs as i128))),
+            (Data::Uint(lhs), Data::Int(rhs)) => Some((*lhs as i128).cmp(&(*rhs as i128))),
+            (Data::Int(lhs), Data::Float(rhs)) => (*lhs as f64).partial_cmp(rhs),
+            (Data::Float(lhs), Data::Int(rhs)) => lhs.partial_cmp(&(*rhs as f64)),
+            (Data::Uint(lhs), Data::Float(rhs)) => (*lhs as f64).partial_cmp(rhs),
+            (Data::Float(lhs), Data::Uint(rhs)) => lhs.partial_cmp(&(*rhs as f64)),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the data selects the branch of an if! or elif! block. 
+    /// Zero, empty strings, empty collections, false, null and empty options are falsy. 
+    pub fn is_truthy(&self) -> bool {
         match self {
-            Data::String(_) => true,
-            Data::Int(_) => true,
-            Data::Uint(_) => true,
-            Data::Float(_) => true,
-            _ => false,
+            Data::String(string) => !string.is_empty(),
+            Data::Int(int) => *int != 0,
+            Data::Uint(uint) => *uint != 0,
+            Data::Float(float) => *float != 0.0,
+            Data::Bool(boolean) => *boolean,
+            Data::Null => false,
+            Data::Array(arr) => !arr.is_empty(),
+            Data::Tuple(tup) => !tup.is_empty(),
+            Data::Object(obj) => !obj.is_empty(),
+            Data::Option(option) => option.as_ref().is_some_and(|data| data.is_truthy()),
         }
     }
 
@@ -72,23 +135,49 @@ impl Data {
         }
     }
 
-    pub fn get(&self, key: &str) -> Data {
+    pub fn get(&self, key: &str) -> Result<Data, String> {
+        self.member(key).map(|member| Data::Option(member.map(|member| Box::new(member.clone()))))
+    }
+
+    /// Borrows the member of an object, None if the object doesn't have the member
+    pub fn member(&self, key: &str) -> Result<Option<&Data>, String> {
         match self {
-            Data::Object(object) => {
-                match object.get(key) {
-                    Some(value) => Data::Option(Some(Box::new(value.clone()))),
-                    None => Data::Option(None),
-                }
-            },
+            Data::Object(object) => Ok(object.get(key)),
             Data::Option(option) => {
                 match option {
-                    Some(object) => object.get(key),
-                    None => panic!("Can't find key '{}' from the option, because the option is none.", key),
+                    Some(object) => object.member(key),
+                    None => Err(format!("Can't find key '{}' from the option, because the option is none.", key)),
                 }
             }
-            _ => panic!("Can't find key '{}' on data that isn't an object.", key),
+            _ => Err(format!("Can't find key '{}' on data that isn't an object.", key)),
         }
     }
+
+    /// Borrows the element of an array or tuple at an integer index, negative indices count from the end. Objects are indexed by their string keys. 
+    /// None if there is no element at the index. 
+    pub fn index(&self, index: &Data) -> Result<Option<&Data>, String> {
+        match (self, index) {
+            (Data::Option(Some(data)), _) => data.index(index),
+            (Data::Option(None), _) => Err(format!("Can't index the option with {:?}, because the option is none.", index)),
+            (_, Data::Option(Some(index))) => self.index(index),
+            (Data::Object(_), Data::String(key)) => self.member(key),
+            (Data::Object(_), _) => Err(format!("Objects can only be indexed with string keys, but found {:?}.", index)),
+            (Data::Array(array), _) => element(array, index),
+            (Data::Tuple(tuple), _) => element(tuple, index),
+            _ => Err(format!("Can't index data that isn't an array, tuple or object with {:?}.", index)),
+        }
+    }
+}
+
+fn element<'a>(elements: &'a [Data], index: &Data) -> Result<Option<&'a Data>, String> {
+    let index = match index {
+        Data::Uint(index) => Some(*index),
+        Data::Int(index) if *index < 0 => elements.len().checked_sub(index.unsigned_abs()),
+        Data::Int(index) => Some(*index as usize),
+        _ => return Err(format!("Arrays and tuples can only be indexed with integers, but found {:?}.", index)),
+    };
+
+    Ok(index.and_then(|index| elements.get(index)))
 }
 
 impl IntoIterator for Data {
@@ -99,12 +188,34 @@ impl IntoIterator for Data {
     fn into_iter(self) -> Self::IntoIter {
         match self {
             Data::Array(arr) => arr.into_iter(),
-            Data::Tuple(tup) => tup.to_vec().into_iter(),
+            Data::Tuple(tup) => tup.into_vec().into_iter(),
             _ => vec![self].into_iter(),
         }
     }
 }
 
+/// Numbers are equal when they hold the same value, regardless of their type: 1 == 1.0
+impl PartialEq for Data {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Data::String(lhs), Data::String(rhs)) => lhs == rhs,
+            (Data::Int(lhs), Data::Int(rhs)) => lhs == rhs,
+            (Data::Uint(lhs), Data::Uint(rhs)) => lhs == rhs,
+            (Data::Float(lhs), Data::Float(rhs)) => lhs == rhs,
+            (Data::Int(int), Data::Uint(uint)) | (Data::Uint(uint), Data::Int(int)) => *int >= 0 && *int as usize == *uint,
+            (Data::Int(int), Data::Float(float)) | (Data::Float(float), Data::Int(int)) => *int as f64 == *float,
+            (Data::Uint(uint), Data::Float(float)) | (Data::Float(float), Data::Uint(uint)) => *uint as f64 == *float,
+            (Data::Bool(lhs), Data::Bool(rhs)) => lhs == rhs,
+            (Data::Null, Data::Null) => true,
+            (Data::Array(lhs), Data::Array(rhs)) => lhs == rhs,
+            (Data::Tuple(lhs), Data::Tuple(rhs)) => lhs == rhs,
+            (Data::Object(lhs), Data::Object(rhs)) => lhs == rhs,
+            (Data::Option(lhs), Data::Option(rhs)) => lhs == rhs,
+            _ => false,
+        }
+    }
+}
+
 impl Debug for Data {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -113,6 +224,8 @@ impl Debug for Data {
             Data::Int(num) => write!(f, "{}", num),
             Data::Uint(num) => write!(f, "{}", num),
             Data::Float(num) => write!(f, "{}", num),
+            Data::Bool(boolean) => write!(f, "{}", boolean),
+            Data::Null => write!(f, "null"),
             Data::Array(array) => write!(f, "{:?}", array),
             Data::Tuple(tuple) => {
                 let mut render = String::from("(");
@@ -121,7 +234,7 @@ impl Debug for Data {
                 }
 
                 // Remove ', ' at end 
-                if tuple.len() > 0 {
+                if !tuple.is_empty() {
                     render.pop();
                     render.pop();
                 }
@@ -148,6 +261,8 @@ impl Renderable for Data {
             Data::Int(num) => num.to_string(),
             Data::Uint(num) => num.to_string(),
             Data::Float(num) => num.to_string(),
+            Data::Bool(boolean) => boolean.to_string(),
+            Data::Null => String::from("null"),
             Data::Array(array) => format!("{:?}", array),
             Data::Tuple(tuple) => {
                 let mut render = String::from("(");
@@ -156,7 +271,7 @@ impl Renderable for Data {
                 }
 
                 // Remove ', ' at end 
-                if tuple.len() > 0 {
+                if !tuple.is_empty() {
                     render.pop();
                     render.pop();
                 }
@@ -174,6 +289,12 @@ pub struct DataContext {
     data: HashMap<String, Data>,
 }
 
+impl Default for DataContext {
+    fn default() -> Self {
+        DataContext::new()
+    }
+}
+
 impl DataContext {
     pub fn new() -> DataContext {
         DataContext {
@@ -0,0 +1,181 @@
+use std::convert::TryFrom;
+
+use crate::common::ExclaimError;
+use crate::data::traits::Renderable;
+use crate::tokens::{
+    Op,
+    Token,
+};
+
+use super::Data;
+
+type OperatorResult = Result<Data, String>;
+
+/// Applies a binary operator to its operands. && and || are evaluated by the runtime, since they don't always evaluate their right hand side.
+pub fn apply_operator(lhs: Data, operator: &Token, rhs: Data) -> Result<Data, ExclaimError> {
+    dispatch_operator(lhs, operator, rhs)
+        .map_err(|msg| ExclaimError::Runtime(operator.location(), msg))
+}
+
+/// Applies a unary operator to its operand
+pub fn apply_unary_operator(operator: &Token, operand: Data) -> Result<Data, ExclaimError> {
+    dispatch_unary_operator(operator, operand)
+        .map_err(|msg| ExclaimError::Runtime(operator.location(), msg))
+}
+
+fn dispatch_unary_operator(operator: &Token, operand: Data) -> OperatorResult {
+    let op = match operator.operator() {
+        Some(op) => op,
+        None => return Err(format!("Expected an operator, but found '{}'.", operator)),
+    };
+
+    match op {
+        Op::Not => Ok(Data::Bool(!operand.is_truthy())),
+        Op::Subtract => negate(unwrap_option(operand)),
+        _ => Err(format!("'{}' is not a unary operator.", op.as_str())),
+    }
+}
+
+fn dispatch_operator(lhs: Data, operator: &Token, rhs: Data) -> OperatorResult {
+    let op = match operator.operator() {
+        Some(op) => op,
+        None => return Err(format!("Expected an operator, but found '{}'.", operator)),
+    };
+
+    // Options are operated on by the value they hold
+    let lhs = unwrap_option(lhs);
+    let rhs = unwrap_option(rhs);
+
+    match op {
+        Op::Equality => Ok(Data::Bool(lhs == rhs)),
+        Op::Inequality => Ok(Data::Bool(lhs != rhs)),
+        Op::Less | Op::LessEqual | Op::Greater | Op::GreaterEqual => compare(op, &lhs, &rhs),
+        Op::Add => add(lhs, rhs),
+        Op::Subtract | Op::Multiply | Op::Divide | Op::Remainder => arithmetic(op, lhs, rhs),
+        _ => Err(format!("'{}' is not a binary operator.", op.as_str())),
+    }
+}
+
+/// The value held by the option, an empty option is null
+fn unwrap_option(data: Data) -> Data {
+    match data {
+        Data::Option(Some(data)) => unwrap_option(*data),
+        Data::Option(None) => Data::Null,
+        data => data,
+    }
+}
+
+fn compare(op: &Op, lhs: &Data, rhs: &Data) -> OperatorResult {
+    let ordering = match lhs.compare(rhs) {
+        Some(ordering) => ordering,
+        // NaN is not ordered with any number
+        None if lhs.is_number() && rhs.is_number() => return Ok(Data::Bool(false)),
+        None => return Err(format!("Unable to compare {:?} with {:?}, '{}' can only compare numbers with numbers or strings with strings.", lhs, rhs, op.as_str())),
+    };
+
+    let result = match op {
+        Op::Less => ordering.is_lt(),
+        Op::LessEqual => ordering.is_le(),
+        Op::Greater => ordering.is_gt(),
+        _ => ordering.is_ge(),
+    };
+
+    Ok(Data::Bool(result))
+}
+
+/// + concatenates the rendered scalars when either of them is a string, otherwise it adds numbers
+fn add(lhs: Data, rhs: Data) -> OperatorResult {
+    let is_string = matches!(lhs, Data::String(_)) || matches!(rhs, Data::String(_));
+    if is_string && lhs.is_scalar() && rhs.is_scalar() {
+        let mut concatenated = lhs.render();
+        concatenated.push_str(&rhs.render());
+        Ok(Data::String(concatenated))
+    } else {
+        arithmetic(&Op::Add, lhs, rhs)
+    }
+}
+
+/// Any Float operand makes the result a Float. Integers are computed exactly, the result is a Uint when
+/// both operands are Uints and the result isn't negative, otherwise it is an Int.
+fn arithmetic(op: &Op, lhs: Data, rhs: Data) -> OperatorResult {
+    match (&lhs, &rhs) {
+        (Data::Int(_), Data::Int(_)) | (Data::Int(_), Data::Uint(_)) | (Data::Uint(_), Data::Int(_)) => {
+            integer_arithmetic(op, integer(&lhs), integer(&rhs), false)
+        },
+        (Data::Uint(_), Data::Uint(_)) => integer_arithmetic(op, integer(&lhs), integer(&rhs), true),
+        (Data::Float(_), _) | (_, Data::Float(_)) if lhs.is_number() && rhs.is_number() => {
+            float_arithmetic(op, float(&lhs), float(&rhs))
+        },
+        _ => Err(format!("Unable to compute {:?} {} {:?}, '{}' only works on numbers.", lhs, op.as_str(), rhs, op.as_str())),
+    }
+}
+
+/// Integers are negated as 0 - operand, so the result is an Int and overflows like a subtraction
+fn negate(operand: Data) -> OperatorResult {
+    match operand {
+        Data::Int(_) | Data::Uint(_) => integer_arithmetic(&Op::Subtract, 0, integer(&operand), false)
+            .map_err(|_| format!("Overflow while negating {}.", integer(&operand))),
+        Data::Float(float) => Ok(Data::Float(-float)),
+        _ => Err(format!("Unable to compute -{:?}, '-' only works on numbers.", operand)),
+    }
+}
+
+fn integer(data: &Data) -> i128 {
+    match data {
+        Data::Int(int) => *int as i128,
+        Data::Uint(uint) => *uint as i128,
+        _ => 0,
+    }
+}
+
+fn float(data: &Data) -> f64 {
+    match data {
+        Data::Int(int) => *int as f64,
+        Data::Uint(uint) => *uint as f64,
+        Data::Float(float) => *float,
+        _ => 0.0,
+    }
+}
+
+fn integer_arithmetic(op: &Op, lhs: i128, rhs: i128, unsigned: bool) -> OperatorResult {
+    if rhs == 0 && matches!(op, Op::Divide | Op::Remainder) {
+        return Err(format!("Division by zero while computing {} {} {}.", lhs, op.as_str(), rhs));
+    }
+
+    let result = match op {
+        Op::Add => lhs.checked_add(rhs),
+        Op::Subtract => lhs.checked_sub(rhs),
+        Op::Multiply => lhs.checked_mul(rhs),
+        Op::Divide => lhs.checked_div(rhs),
+        _ => lhs.checked_rem(rhs),
+    };
+
+    let result = match result {
+        Some(result) if unsigned && result >= 0 => usize::try_from(result).ok().map(Data::Uint),
+        Some(result) => isize::try_from(result).ok().map(Data::Int),
+        None => None,
+    };
+
+    result.ok_or_else(|| format!("Overflow while computing {} {} {}.", lhs, op.as_str(), rhs))
+}
+
+fn float_arithmetic(op: &Op, lhs: f64, rhs: f64) -> OperatorResult {
+    if rhs == 0.0 && matches!(op, Op::Divide | Op::Remainder) {
+        return Err(format!("Division by zero while computing {} {} {}.", lhs, op.as_str(), rhs));
+    }
+
+    let result = match op {
+        Op::Add => lhs + rhs,
+        Op::Subtract => lhs - rhs,
+        Op::Multiply => lhs * rhs,
+        Op::Divide => lhs / rhs,
+        _ => lhs % rhs,
+    };
+
+    // Finite operands only give an infinite result when it is too large for a Float
+    if result.is_infinite() && lhs.is_finite() && rhs.is_finite() {
+        return Err(format!("Overflow while computing {} {} {}.", lhs, op.as_str(), rhs));
+    }
+
+    Ok(Data::Float(result))
+}
@@ -1,27 +1,36 @@
 use std::collections::BTreeMap;
 
 use crate::{ast::transforms::Transform, data::traits::Renderable};
+use crate::common::ExclaimError;
 
 use super::Data;
 
-pub fn apply_transform(data: Data, transform: &Transform, arguments: Vec<Data>) -> Data {
+type TransformResult = Result<Data, String>;
+
+pub fn apply_transform(data: Data, transform: &Transform, arguments: Vec<Data>) -> Result<Data, ExclaimError> {
+    dispatch_transform(data, transform, arguments)
+        .map_err(|msg| ExclaimError::Transform(transform.location(), msg))
+}
+
+fn dispatch_transform(data: Data, transform: &Transform, arguments: Vec<Data>) -> TransformResult {
     // match transform signature: (name, num_arguments)
     match transform.name() {
         "array" => array(data),
+        "bool" => boolean(data),
         "chars" => chars(data),
         "concat" => {
             match transform.num_arguments() {
                 0 => concat(data),
-                1 => concat_scalar(data, arguments.get(0).unwrap()),
-                _ => panic!("Wrong number of arguments for concat"),
+                1 => concat_scalar(data, arguments.first().unwrap()),
+                _ => Err("Wrong number of arguments for concat".to_string()),
             }
         }
         "enumerate" => enumerate(data),
         "float" => float(data),
         "get" => {
             match transform.num_arguments() {
-                1 => get(data, arguments.get(0).unwrap()),
-                _ => panic!("Wrong number of arguments for get"),
+                1 => get(data, arguments.first().unwrap()),
+                _ => Err("Wrong number of arguments for get".to_string()),
             }
         },
         "int" => int(data),
@@ -31,9 +40,9 @@ pub fn apply_transform(data: Data, transform: &Transform, arguments: Vec<Data>)
         "string" => string(data),
         "take" => {
             match transform.num_arguments() {
-                1 => take(data, arguments.get(0).unwrap()),
-                2 => take_lower_upper(data, arguments.get(0).unwrap(), arguments.get(1).unwrap()),
-                _ => panic!("Wrong number of arguments for take"),
+                1 => take(data, arguments.first().unwrap()),
+                2 => take_lower_upper(data, arguments.first().unwrap(), arguments.get(1).unwrap()),
+                _ => Err("Wrong number of arguments for take".to_string()),
             }
         },
         "tuple" => tuple(data),
@@ -42,16 +51,16 @@ pub fn apply_transform(data: Data, transform: &Transform, arguments: Vec<Data>)
         "uppercase" => uppercase(data),
 
         // Reserved transformation names
-        "map" | "filter" | "reduce" => panic!("Transformation is reserved."),
-        _ => panic!("Transform '{:?}' does not exist.", transform),
+        "map" | "filter" | "reduce" => Err("Transformation is reserved.".to_string()),
+        name => Err(format!("Transform '{}' does not exist.", name)),
     }
 }
 
-fn array(data: Data) -> Data {
+fn array(data: Data) -> TransformResult {
     match data {
-        Data::String(_) | Data::Int(_) | Data::Uint(_) | Data::Float(_) => panic!("Unable to call `array` on scalar types."),
+        Data::String(_) | Data::Int(_) | Data::Uint(_) | Data::Float(_) | Data::Bool(_) | Data::Null => Err("Unable to call `array` on scalar types.".to_string()),
         Data::Tuple(tuple) => {
-            Data::Array(tuple.to_vec())
+            Ok(Data::Array(tuple.into_vec()))
         },
         Data::Object(object) => {
             let mut array = vec![];
@@ -61,21 +70,39 @@ fn array(data: Data) -> Data {
                 array.push(pair);
             }
 
-            Data::Array(array)
+            Ok(Data::Array(array))
         },
-        Data::Array(_) => data,
-        Data::Option(_) => panic!("Unable to call `array` on wrapper types.")
+        Data::Array(_) => Ok(data),
+        Data::Option(_) => Err("Unable to call `array` on wrapper types.".to_string())
     }
 }
 
-fn chars(data: Data) -> Data {
+fn boolean(data: Data) -> TransformResult {
     match data {
-        Data::String(string) => Data::Array(string.chars().map(|c| Data::String(c.to_string())).collect()),
-        _ => panic!("chars expects string as input")
+        Data::Bool(_) => Ok(data),
+        Data::String(string) => {
+            match string.parse::<bool>() {
+                Ok(boolean) => Ok(Data::Bool(boolean)),
+                Err(_) => Err(format!("Unable to parse \"{}\" as a bool.", string)),
+            }
+        }
+        Data::Uint(uint) => Ok(Data::Bool(uint != 0)),
+        Data::Int(int) => Ok(Data::Bool(int != 0)),
+        Data::Float(float) => Ok(Data::Bool(float != 0.0)),
+        Data::Null => Err("Unable to call `bool` transformation on null.".to_string()),
+        Data::Array(_) | Data::Tuple(_) | Data::Object(_) => Err("Unable to call `bool` transformation on compound types.".to_string()),
+        Data::Option(_) => Err("Unable to call `bool` transformation on wrapper types.".to_string()),
     }
 }
 
-fn concat(data: Data) -> Data {
+fn chars(data: Data) -> TransformResult {
+    match data {
+        Data::String(string) => Ok(Data::Array(string.chars().map(|c| Data::String(c.to_string())).collect())),
+        _ => Err("chars expects string as input".to_string())
+    }
+}
+
+fn concat(data: Data) -> TransformResult {
     match data {
         Data::Array(array) => {
             let mut concatenated = String::new();
@@ -84,238 +111,261 @@ fn concat(data: Data) -> Data {
                 if data.is_scalar() {
                     concatenated.push_str(&data.render())
                 } else {
-                    panic!("Found non-scalar element while concatenating an array")
+                    return Err("Found non-scalar element while concatenating an array".to_string())
                 }
             }
 
-            Data::String(concatenated)
+            Ok(Data::String(concatenated))
         },
-        _ => panic!("chars expects string as input")
+        _ => Err("concat expects an array as input".to_string())
     }
 }
 
-fn concat_scalar(mut data: Data, scalar: &Data) -> Data {
+fn concat_scalar(mut data: Data, scalar: &Data) -> TransformResult {
     let scalar = match scalar {
         Data::String(string) => string.to_string(),
         Data::Int(int) => int.to_string(),
         Data::Uint(uint) => uint.to_string(),
         Data::Float(float) => float.to_string(),
-        _ => panic!("Concat can only take scalars as an argument"),
+        Data::Bool(boolean) => boolean.to_string(),
+        Data::Null => String::from("null"),
+        _ => return Err("Concat can only take scalars as an argument".to_string()),
     };
 
     match &mut data {
         Data::String(string) => {
             string.push_str(&scalar);
         },
-        _ => panic!("concat expects string as input")
+        _ => return Err("concat expects string as input".to_string())
     }
 
-    data
+    Ok(data)
 }
 
-fn enumerate(data: Data) -> Data {
+fn enumerate(data: Data) -> TransformResult {
     match data {
         Data::Array(array) => {
             let mut enumerated_array = vec![];
-            let mut index = 0; 
             
-            for data in array {
+            for (index, data) in array.into_iter().enumerate() {
                 enumerated_array.push(Data::Tuple(Box::new([data, Data::Uint(index)])));
-                index += 1;
             }
 
-            Data::Array(enumerated_array)
+            Ok(Data::Array(enumerated_array))
         },
-        _ => panic!("enumerate expects an array as input.")
+        _ => Err("enumerate expects an array as input.".to_string())
     }
 }
 
-fn float(data: Data) -> Data {
+fn float(data: Data) -> TransformResult {
     match data {
-        Data::Float(_) => data,
+        Data::Float(_) => Ok(data),
         Data::String(string) => {
-            let number: f64 = string.parse().unwrap();
-            Data::Float(number)
+            match string.parse::<f64>() {
+                Ok(number) => Ok(Data::Float(number)),
+                Err(_) => Err(format!("Unable to parse \"{}\" as a float.", string)),
+            }
         }
         Data::Uint(uint) => {
-            Data::Float(uint as f64)
+            Ok(Data::Float(uint as f64))
         }
         Data::Int(int) => {
-            Data::Float(int as f64)
+            Ok(Data::Float(int as f64))
+        }
+        Data::Bool(boolean) => {
+            Ok(Data::Float(if boolean { 1.0 } else { 0.0 }))
         }
-        Data::Array(_) | Data::Tuple(_) | Data::Object(_) => panic!("Unable to call `float` transformation on compound types."),
-        Data::Option(_) => panic!("Unable to call `float` transformation on wrapper types."),
+        Data::Null => Err("Unable to call `float` transformation on null.".to_string()),
+        Data::Array(_) | Data::Tuple(_) | Data::Object(_) => Err("Unable to call `float` transformation on compound types.".to_string()),
+        Data::Option(_) => Err("Unable to call `float` transformation on wrapper types.".to_string()),
     }
 }
 
-fn get(data: Data, key: &Data) -> Data {
+fn get(data: Data, key: &Data) -> TransformResult {
     match key {
         Data::String(key) => {
             match data {
                 Data::Object(object) => {
                     match object.get(key) {
-                        Some(value) => Data::Option(Some(Box::new(value.clone()))),
-                        None => Data::Option(None),
+                        Some(value) => Ok(Data::Option(Some(Box::new(value.clone())))),
+                        None => Ok(Data::Option(None)),
                     }
                 },
-                _ => panic!("get does not transform the given data: {:?}", data)
+                _ => Err(format!("get does not transform the given data: {:?}", data))
             }
         },
         Data::Uint(index) => {
             match data {
                 Data::Array(array) => {
                     if *index >= array.len() {
-                        return Data::Option(None)
+                        return Ok(Data::Option(None))
                     }
 
-                    Data::Option(Some(Box::new(array[*index].clone())))
+                    Ok(Data::Option(Some(Box::new(array[*index].clone()))))
                 }    
                 Data::Tuple(tuple) => {
                     if *index >= tuple.len() {
-                        return Data::Option(None)
+                        return Ok(Data::Option(None))
                     }
         
-                    Data::Option(Some(Box::new(tuple[*index].clone())))
+                    Ok(Data::Option(Some(Box::new(tuple[*index].clone()))))
                 }
-                _ => panic!("get does not transform the given data: {:?}", data),
+                _ => Err(format!("get does not transform the given data: {:?}", data)),
             }
         }
-        _ => panic!("get only takes a string as an argument: {:?}.", key)
+        _ => Err(format!("get only takes a string as an argument: {:?}.", key))
     }
 }
 
-fn int(data: Data) -> Data {
+fn int(data: Data) -> TransformResult {
     match data {
-        Data::Int(_) => data,
+        Data::Int(_) => Ok(data),
         Data::String(string) => {
-            let number: isize = string.parse().unwrap();
-            Data::Int(number)
+            match string.parse::<isize>() {
+                Ok(number) => Ok(Data::Int(number)),
+                Err(_) => Err(format!("Unable to parse \"{}\" as an int.", string)),
+            }
         }
         Data::Uint(uint) => {
-            Data::Int(uint as isize)
+            Ok(Data::Int(uint as isize))
         }
         Data::Float(float) => {
-            Data::Int(float as isize)
+            Ok(Data::Int(float as isize))
         }
-        Data::Array(_) | Data::Tuple(_) | Data::Object(_) => panic!("Unable to call `int` transformation on compound types."),
-        Data::Option(_) => panic!("Unable to call `int` transformation on wrapper types."),
+        Data::Bool(boolean) => {
+            Ok(Data::Int(boolean as isize))
+        }
+        Data::Null => Err("Unable to call `int` transformation on null.".to_string()),
+        Data::Array(_) | Data::Tuple(_) | Data::Object(_) => Err("Unable to call `int` transformation on compound types.".to_string()),
+        Data::Option(_) => Err("Unable to call `int` transformation on wrapper types.".to_string()),
     }
 }
 
-fn len(data: Data) -> Data {
+fn len(data: Data) -> TransformResult {
     let length = match data {
         Data::String(string) => string.len(),
-        Data::Int(_) => panic!("Unable to call `len` on Int."),
-        Data::Uint(_) => panic!("Unable to call `len` on Uint."),
-        Data::Float(_) => panic!("Unable to call `len` on Float."),
+        Data::Int(_) => return Err("Unable to call `len` on Int.".to_string()),
+        Data::Uint(_) => return Err("Unable to call `len` on Uint.".to_string()),
+        Data::Float(_) => return Err("Unable to call `len` on Float.".to_string()),
+        Data::Bool(_) => return Err("Unable to call `len` on Bool.".to_string()),
+        Data::Null => return Err("Unable to call `len` on Null.".to_string()),
         Data::Array(array) => array.len(),
         Data::Tuple(tuple) => tuple.len(),
-        Data::Object(_) => panic!("Unable to call `len` on Object."),
-        Data::Option(_) => panic!("Unable to call `len` on wrapper types."),
+        Data::Object(_) => return Err("Unable to call `len` on Object.".to_string()),
+        Data::Option(_) => return Err("Unable to call `len` on wrapper types.".to_string()),
     };
 
-    Data::Uint(length)
+    Ok(Data::Uint(length))
 }
 
-fn lowercase(data: Data) -> Data {
+fn lowercase(data: Data) -> TransformResult {
     match data {
-        Data::String(string) => Data::String(string.to_lowercase()),
-        _ => panic!("Cannot transform input to lowercase"),
+        Data::String(string) => Ok(Data::String(string.to_lowercase())),
+        _ => Err("Cannot transform input to lowercase".to_string()),
     }
 }
 
-fn object(data: Data) -> Data {
+fn object(data: Data) -> TransformResult {
     match data {
-        Data::String(_) | Data::Int(_) | Data::Uint(_) | Data::Float(_) => panic!("Unable to call `array` on scalar types."),
+        Data::String(_) | Data::Int(_) | Data::Uint(_) | Data::Float(_) | Data::Bool(_) | Data::Null => Err("Unable to call `object` on scalar types.".to_string()),
         Data::Tuple(tuple) => {
             let mut object = BTreeMap::new();
             for (index, item) in tuple.iter().enumerate() {
                 object.insert(index.to_string(), item.clone());
             }
 
-            Data::Object(object)
+            Ok(Data::Object(object))
         },
-        Data::Object(_) => data,
+        Data::Object(_) => Ok(data),
         Data::Array(array) => {
             let mut object = BTreeMap::new();
             for (index, item) in array.iter().enumerate() {
                 object.insert(index.to_string(), item.clone());
             }
 
-            Data::Object(object)
+            Ok(Data::Object(object))
         },
-        Data::Option(_) => panic!("Unable to call `object` on wrapper types.")
+        Data::Option(_) => Err("Unable to call `object` on wrapper types.".to_string())
     }
 }
 
-fn string(data: Data) -> Data {
+fn string(data: Data) -> TransformResult {
     match data {
-        Data::String(_) => data,
+        Data::String(_) => Ok(data),
         Data::Uint(uint) => {
-            Data::String(uint.to_string())
+            Ok(Data::String(uint.to_string()))
         }
         Data::Int(int) => {
-            Data::String(int.to_string())
+            Ok(Data::String(int.to_string()))
         }
         Data::Float(float) => {
-            Data::String(float.to_string())
+            Ok(Data::String(float.to_string()))
+        }
+        Data::Bool(boolean) => {
+            Ok(Data::String(boolean.to_string()))
         }
-        Data::Tuple(_) | Data::Object(_) | Data::Array(_) => panic!("Unable to call `string` on compound types."),
-        Data::Option(_) => panic!("Unable to call `string` on wrapper types."),
+        Data::Null => Ok(Data::String(String::from("null"))),
+        Data::Tuple(_) | Data::Object(_) | Data::Array(_) => Err("Unable to call `string` on compound types.".to_string()),
+        Data::Option(_) => Err("Unable to call `string` on wrapper types.".to_string()),
     }
 }
 
-fn take(data: Data, uint: &Data) -> Data {
+fn take(data: Data, uint: &Data) -> TransformResult {
     let take = match uint {
         Data::Uint(num) => *num,
-        _ => panic!("at only takes a unsigned integer as an argument: {:?}.", uint)
+        _ => return Err(format!("take only takes a unsigned integer as an argument: {:?}.", uint))
     };
 
     match data {
-        Data::Array(array) => {
-            let take_slice = array.split_at(take).0;
-            let mut new_array = Vec::with_capacity(take_slice.len());
-            new_array.clone_from(&take_slice.to_vec());
-            Data::Array(new_array)
+        Data::Array(mut array) => {
+            if take > array.len() {
+                return Err(format!("Take is greater than the length of the array: {} > {}", take, array.len()))
+            }
+
+            array.truncate(take);
+            Ok(Data::Array(array))
         },
-        _ => panic!("take does not transform the given data: {:?}", data)
+        _ => Err(format!("take does not transform the given data: {:?}", data))
     }
 }
 
-fn take_lower_upper(data: Data, lower: &Data, upper: &Data) -> Data {
+fn take_lower_upper(data: Data, lower: &Data, upper: &Data) -> TransformResult {
     let lower = match lower {
         Data::Uint(num) => *num,
-        _ => panic!("take only takes a unsigned integer as an argument: {:?}.", lower)
+        _ => return Err(format!("take only takes a unsigned integer as an argument: {:?}.", lower))
     };
 
     let upper = match upper {
         Data::Uint(num) => *num,
-        _ => panic!("take only takes a unsigned integer as an argument: {:?}.", upper)
+        _ => return Err(format!("take only takes a unsigned integer as an argument: {:?}.", upper))
     };
 
     match data {
         Data::Array(array) => {
             if lower >= array.len() {
-                panic!("Lower range is greater than the length of the array: {} >= {}", upper, array.len())
+                return Err(format!("Lower range is greater than the length of the array: {} >= {}", lower, array.len()))
             }
             // Only Greater Than since the upper bound is exclusive
             if upper > array.len() {
-                panic!("Upper range is greater than the length of the array: {} >= {}", upper, array.len())
+                return Err(format!("Upper range is greater than the length of the array: {} > {}", upper, array.len()))
+            }
+            if lower > upper {
+                return Err(format!("Lower range is greater than the upper range: {} > {}", lower, upper))
             }
 
             let sub_array = array[lower..upper].to_vec();
 
-            Data::Array(sub_array)
+            Ok(Data::Array(sub_array))
         },
-        _ => panic!("take does not transform the given data: {:?}", data)
+        _ => Err(format!("take does not transform the given data: {:?}", data))
     }
 }
 
 
-fn tuple(data: Data) -> Data {
+fn tuple(data: Data) -> TransformResult {
     match data {
-        Data::String(_) | Data::Int(_) | Data::Uint(_) | Data::Float(_) => panic!("Unable to call `array` on scalar types."),
-        Data::Tuple(_) => data,
+        Data::String(_) | Data::Int(_) | Data::Uint(_) | Data::Float(_) | Data::Bool(_) | Data::Null => Err("Unable to call `tuple` on scalar types.".to_string()),
+        Data::Tuple(_) => Ok(data),
         Data::Object(object) => {
             let mut keys = vec![];
             let mut values = vec![];
@@ -328,53 +378,60 @@ fn tuple(data: Data) -> Data {
             let keys = Data::Array(keys);
             let values = Data::Array(values);
 
-            Data::Tuple(Box::new([keys, values]))
+            Ok(Data::Tuple(Box::new([keys, values])))
         },
         Data::Array(array) => {
-            Data::Tuple(array.into_boxed_slice())
+            Ok(Data::Tuple(array.into_boxed_slice()))
         },
-        Data::Option(_) => panic!("Unable to call `tuple` on wrapper types.")
+        Data::Option(_) => Err("Unable to call `tuple` on wrapper types.".to_string())
     }
 }
 
-fn uint(data: Data) -> Data {
+fn uint(data: Data) -> TransformResult {
     match data {
-        Data::Uint(_) => data,
+        Data::Uint(_) => Ok(data),
         Data::String(string) => {
-            let number: usize = string.parse().unwrap();
-            Data::Uint(number)
+            match string.parse::<usize>() {
+                Ok(number) => Ok(Data::Uint(number)),
+                Err(_) => Err(format!("Unable to parse \"{}\" as an unsigned integer.", string)),
+            }
         }
         Data::Int(int) => {
             if int < 0 {
-                panic!("Unable to transform a negative integer into an unsigned integer")
+                return Err("Unable to transform a negative integer into an unsigned integer".to_string())
             }
-            Data::Uint(int as usize)
+            Ok(Data::Uint(int as usize))
         }
         Data::Float(float) => {
             if float < 0.0 {
-                panic!("Unable to transform a negative float into an unsigned integer")
+                return Err("Unable to transform a negative float into an unsigned integer".to_string())
             }
-            Data::Uint(float as usize)
+            Ok(Data::Uint(float as usize))
+        }
+        Data::Bool(boolean) => {
+            Ok(Data::Uint(boolean as usize))
         }
-        _ => panic!("unimplemented"),
+        Data::Null => Err("Unable to call `uint` transformation on null.".to_string()),
+        Data::Array(_) | Data::Tuple(_) | Data::Object(_) => Err("Unable to call `uint` transformation on compound types.".to_string()),
+        Data::Option(_) => Err("Unable to call `uint` transformation on wrapper types.".to_string()),
     }
 }
 
-fn unwrap(data: Data) -> Data {
+fn unwrap(data: Data) -> TransformResult {
     match data {
         Data::Option(option) => {
             match option {
-                Some(value) => *value, // Deref the Box<T>
-                None => panic!("Tried to unwrap nothing!"),
+                Some(value) => Ok(*value), // Deref the Box<T>
+                None => Err("Tried to unwrap nothing!".to_string()),
             }
         }
-        _ => panic!("unwrap can only transform Options."),
+        _ => Err("unwrap can only transform Options.".to_string()),
     }
 }
 
-fn uppercase(data: Data) -> Data {
+fn uppercase(data: Data) -> TransformResult {
     match data {
-        Data::String(string) => Data::String(string.to_uppercase()),
-        _ => panic!("Cannot transform input to uppercase"),
+        Data::String(string) => Ok(Data::String(string.to_uppercase())),
+        _ => Err("Cannot transform input to uppercase".to_string()),
     }
 }
\ No newline at end of file
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+
+use crate::common::ExclaimError;
+use crate::data::DataContext;
+use crate::options::CompileOptions;
+use crate::template::Template;
+
+use crate::runtime;
+
+/// A collection of named templates that can include each other with include!.
+pub struct Environment {
+    templates: HashMap<String, Template>,
+}
+
+impl Environment {
+    pub fn new() -> Environment {
+        Environment {
+            templates: HashMap::new(),
+        }
+    }
+
+    /// Compiles the source and registers it under name, replacing any template with the same name.
+    pub fn add_template<S: Into<String>>(&mut self, name: S, source: &str) -> Result<(), ExclaimError> {
+        self.add_template_with_options(name, source, &CompileOptions::default())
+    }
+
+    pub fn add_template_with_options<S: Into<String>>(&mut self, name: S, source: &str, options: &CompileOptions) -> Result<(), ExclaimError> {
+        let name = name.into();
+        let template = Template::compile_with_options(source, options)
+            .map_err(|e| e.map_message(|msg| format!("In template '{}': {}", name, msg)))?;
+
+        self.templates.insert(name, template);
+        Ok(())
+    }
+
+    pub fn insert_template<S: Into<String>>(&mut self, name: S, template: Template) -> Option<Template> {
+        self.templates.insert(name.into(), template)
+    }
+
+    pub fn get_template(&self, name: &str) -> Option<&Template> {
+        self.templates.get(name)
+    }
+
+    pub fn render(&self, name: &str, data: &DataContext) -> Result<String, ExclaimError> {
+        let mut output = String::new();
+        self.render_to(name, data, &mut output)?;
+        Ok(output)
+    }
+
+    pub fn render_to<W: fmt::Write>(&self, name: &str, data: &DataContext, sink: &mut W) -> Result<(), ExclaimError> {
+        runtime::run_environment_to(self, name, Some(data), sink)
+    }
+
+    /// Renders the named template into an io::Write sink, see Template::write_to.
+    pub fn write_to<W: io::Write>(&self, name: &str, data: &DataContext, writer: &mut W) -> Result<(), ExclaimError> {
+        let mut sink = runtime::IoSink::new(writer);
+        let result = runtime::run_environment_to(self, name, Some(data), &mut sink);
+        sink.finish(result)
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Environment::new()
+    }
+}
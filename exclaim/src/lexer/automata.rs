@@ -1,123 +1,332 @@
+use std::borrow::Cow;
+
 use crate::tokens::Token;
-use crate::common::Location;
+use crate::common::{
+    Location,
+    Span,
+};
 
-pub struct StackMachine {
-    chars: Vec<char>,
+/// Walks over the borrowed input by byte offset. 
+/// The stack is a slice of the input, it is only copied into an owned buffer when it stops being contiguous (escape sequences, skipped characters).
+pub struct StackMachine<'a> {
+    input: &'a str,
+    // Byte offset of the current character, and the character itself
     index: usize,
-    stack: String,
-    tokens: Vec<Token>,
+    ch: Option<char>,
+    // Byte range of the stack in the input 
+    stack_start: usize,
+    stack_end: usize,
+    // Holds the stack once it can't be borrowed from the input anymore 
+    owned: Option<String>,
+    tokens: Vec<Token<'a>>,
     // Keeps track of start location of a token 
     start: Location,
     // Keeps track of current location in the input 
     current: Location,
+    // Set by a trim marker '-}}', the whitespace at the start of the following text is skipped 
+    trim_next: bool,
+    // Delimiters opening and closing a code block 
+    open: String,
+    close: String,
+    // Drops the '\r' of "\r\n" line endings 
+    normalize_line_endings: bool,
 }
 
-impl StackMachine {
-    pub fn new<S: AsRef<str>>(input: S) -> StackMachine {
+impl<'a> StackMachine<'a> {
+    pub fn new(input: &'a str) -> StackMachine<'a> {
+        // A byte order mark is not part of the template 
+        let index = if input.starts_with('\u{FEFF}') { '\u{FEFF}'.len_utf8() } else { 0 };
+
         StackMachine {
-            chars: input.as_ref().chars().collect(),
-            index: 0,
-            stack: String::new(),
+            input,
+            index,
+            ch: input[index..].chars().next(),
+            stack_start: index,
+            stack_end: index,
+            owned: None,
             tokens: Vec::new(),
-            start: Location::new(0, 0),
-            current: Location::new(0, 0)
+            start: Location::with_offset(0, 0, index),
+            current: Location::with_offset(0, 0, index),
+            trim_next: false,
+            open: String::from("{{"),
+            close: String::from("}}"),
+            normalize_line_endings: false,
         }
     }
 
+    pub fn with_delimiters(mut self, open: &str, close: &str) -> StackMachine<'a> {
+        self.open = open.to_string();
+        self.close = close.to_string();
+        self
+    }
+
+    pub fn with_normalized_line_endings(mut self, enabled: bool) -> StackMachine<'a> {
+        self.normalize_line_endings = enabled;
+        self
+    }
+
+    /// Returns true if the current character is the '\r' of a "\r\n" line ending that should be dropped
+    pub fn at_dropped_carriage_return(&self) -> bool {
+        self.normalize_line_endings && self.ch == Some('\r') && self.lookahead() == Some('\n')
+    }
+
+    pub fn open_delimiter(&self) -> &str {
+        &self.open
+    }
+
+    pub fn close_delimiter(&self) -> &str {
+        &self.close
+    }
+
+    /// Length of the open delimiter in characters
+    pub fn open_len(&self) -> usize {
+        self.open.chars().count()
+    }
+
+    /// Length of the close delimiter in characters
+    pub fn close_len(&self) -> usize {
+        self.close.chars().count()
+    }
+
+    /// Returns true if the open delimiter starts n characters after the current character
+    pub fn at_block_open(&self, n: usize) -> bool {
+        self.starts_with_at(n, &self.open)
+    }
+
+    /// Returns true if the close delimiter starts n characters after the current character
+    pub fn at_block_close(&self, n: usize) -> bool {
+        self.starts_with_at(n, &self.close)
+    }
+
     pub fn view_stack(&self) -> &str {
-        self.stack.as_str()
+        match &self.owned {
+            Some(owned) => owned.as_str(),
+            None => &self.input[self.stack_start..self.stack_end],
+        }
+    }
+
+    /// Takes the stack for a token, borrowed from the input unless it had to be copied into the owned buffer
+    pub fn take_stack(&mut self) -> Cow<'a, str> {
+        match self.owned.take() {
+            Some(mut owned) => {
+                // The buffer grew while pushing, the token only keeps the text
+                owned.shrink_to_fit();
+                Cow::Owned(owned)
+            },
+            None => Cow::Borrowed(&self.input[self.stack_start..self.stack_end]),
+        }
     }
 
     pub fn peek(&self) -> char {
-        self.chars[self.index]
+        self.ch.expect("peek() called at the end of the input")
     }
 
-    pub fn lookahead(&self) -> Option<&char> {
-        self.chars.get(self.index + 1)
+    pub fn lookahead(&self) -> Option<char> {
+        self.lookahead_nth(1)
+    }
+
+    /// Character n positions after the current character. lookahead_nth(1) is the same as lookahead()
+    pub fn lookahead_nth(&self, n: usize) -> Option<char> {
+        self.input[self.offset_nth(n)?..].chars().next()
+    }
+
+    /// Byte offset of the character n positions after the current character 
+    fn offset_nth(&self, n: usize) -> Option<usize> {
+        let mut offset = self.index;
+        for _ in 0..n {
+            offset += self.input[offset..].chars().next()?.len_utf8();
+        }
+        Some(offset)
+    }
+
+    /// Returns true if the pattern starts n characters after the current character 
+    pub fn starts_with_at(&self, n: usize, pattern: &str) -> bool {
+        self.offset_nth(n)
+            .map(|offset| self.input.as_bytes()[offset..].starts_with(pattern.as_bytes()))
+            .unwrap_or_default()
+    }
+
+    /// The input from n characters after the current character, empty past the end of the input 
+    pub fn ahead(&self, n: usize) -> &'a str {
+        self.offset_nth(n).map(|offset| &self.input[offset..]).unwrap_or_default()
+    }
+
+    /// Returns true if the pattern is found anywhere from n characters after the current character
+    pub fn ahead_contains(&self, n: usize, pattern: &str) -> bool {
+        self.offset_nth(n)
+            .map(|offset| self.input[offset..].contains(pattern))
+            .unwrap_or_default()
+    }
+
+    /// Moves to the character len bytes after the current character 
+    fn advance(&mut self, len: usize) {
+        self.index += len;
+        self.ch = self.input[self.index..].chars().next();
+    }
+
+    /// Copies the stack into the owned buffer, so characters that are not next to it in the input can be pushed
+    fn own_stack(&mut self) -> &mut String {
+        let (input, start, end) = (self.input, self.stack_start, self.stack_end);
+        self.owned.get_or_insert_with(|| input[start..end].to_string())
+    }
+
+    fn reset_stack(&mut self) {
+        self.stack_start = self.index;
+        self.stack_end = self.index;
+        self.owned = None;
     }
 
     pub fn skip(&mut self) {
-        self.index += 1;
+        let ch = self.peek();
+        self.advance(ch.len_utf8());
 
         // Shift both locations up
-        self.start.shift();
-        self.current.shift();
+        self.start.shift(ch);
+        self.current.shift(ch);
     }
 
     /// Skips the current character and only shifts the current location. Start location does not change.
     pub fn skip_current(&mut self) {
-        self.index += 1;
+        let ch = self.peek();
+        self.advance(ch.len_utf8());
 
-        // Shift both locations up
-        self.current.shift();
+        // Shift just current location
+        self.current.shift(ch);
+    }
+
+    pub fn skip_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.skip();
+        }
+    }
+
+    pub fn push_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.push();
+        }
+    }
+
+    /// Pushes ch onto the stack in place of the next len characters of the input, e.g. an escape sequence
+    pub fn push_escaped(&mut self, ch: char, len: usize) {
+        self.own_stack().push(ch);
+        let offset = self.offset_nth(len).unwrap_or(self.input.len());
+
+        // Shift just current location
+        for escaped in self.input[self.index..offset].chars() {
+            self.current.shift(escaped);
+        }
+        self.advance(offset - self.index);
     }
 
     pub fn push(&mut self) {
-        let ch = self.chars[self.index];
-        self.stack.push(ch);
-        self.index += 1;
+        let ch = self.peek();
+        let len = ch.len_utf8();
+        if self.owned.is_none() && self.stack_start == self.stack_end {
+            // Fresh stack, borrow from the current character
+            self.stack_start = self.index;
+            self.stack_end = self.index + len;
+        } else if self.owned.is_none() && self.stack_end == self.index {
+            // The character is next to the stack, keep borrowing
+            self.stack_end += len;
+        } else {
+            self.own_stack().push(ch);
+        }
+        self.advance(len);
 
         // Shift just current location
-        self.current.shift();
+        self.current.shift(ch);
     }
 
     pub fn newline(&mut self) {
         self.current.newline();
+
+        // Skipped newline, the next token starts on the new line
+        if self.empty() {
+            self.start = self.current;
+        }
     }
 
     pub fn location(&self) -> Location {
-        self.start.clone()
+        self.start
+    }
+
+    /// Span from the start location of the token to the current location
+    pub fn span(&self) -> Span {
+        Span::new(self.start, self.current)
     }
 
-    pub fn accept_token(&mut self, token: Token) {
+    pub fn accept_token(&mut self, token: Token<'a>) {
         self.tokens.push(token);
 
         // Set for new fresh token
-        self.stack = String::new();
+        self.reset_stack();
         self.start = self.current;
     }
 
-    pub fn get_tokens(self) -> Vec<Token> {
-        self.tokens
+    pub fn last_token(&self) -> Option<&Token<'a>> {
+        self.tokens.last()
     }
 
-    pub fn empty(&self) -> bool {
-        self.stack.len() == 0
+    pub fn pop_token(&mut self) -> Option<Token<'a>> {
+        self.tokens.pop()
     }
 
-    pub fn eof(&self) -> bool {
-        self.index >= self.chars.len()
+    /// Empties the stack without accepting a token 
+    pub fn discard_stack(&mut self) {
+        self.reset_stack();
+        self.start = self.current;
     }
 
-    pub fn debug_line(&self, underline_msg: &str) -> (Location, String) {
-        let mut index = self.index; 
-        let mut line = String::new();
+    /// Removes the trailing whitespace of the last token if it is text. The token is dropped if nothing is left of it.
+    pub fn trim_last_text(&mut self) {
+        let input = self.input;
+        if let Some(Token::StringLiteral(text, span)) = self.tokens.last_mut() {
+            // Whitespace is never escaped, so the trimmed whitespace is also at the end of the span in the input
+            let trimmed = text.len() - text.trim_end().len();
+            let end = span.end().offset() - trimmed;
+            let line_start = input[..end].rfind('\n').map(|newline| newline + 1).unwrap_or(0);
+            let line = span.end().line() - text[text.len() - trimmed..].matches('\n').count();
+            let column = input[line_start..end].chars().count();
+            *span = Span::new(span.start(), Location::with_offset(line, column, end));
 
-        // Backup to get starting character of the line 
-        for idx in (0..=index).rev() {
-            if self.chars[idx] == '\n' {
-                break;
+            let len = text.len() - trimmed;
+            match text {
+                Cow::Borrowed(borrowed) => *borrowed = &borrowed[..len],
+                Cow::Owned(owned) => owned.truncate(len),
             }
-            index = idx;
-        }
-
-        // Collect every character until we reach newline or eof 
-        while let Some(ch) = self.chars.get(index) {
-            if *ch == '\n' {
-                break;
+            if text.is_empty() {
+                self.tokens.pop();
             }
-            line.push(self.chars[index]);
-            index += 1;
         }
+    }
+
+    pub fn set_trim_next(&mut self) {
+        self.trim_next = true;
+    }
+
+    /// Returns true once after set_trim_next() was called 
+    pub fn take_trim_next(&mut self) -> bool {
+        std::mem::take(&mut self.trim_next)
+    }
+
+    pub fn get_tokens(self) -> Vec<Token<'a>> {
+        self.tokens
+    }
+
+    pub fn empty(&self) -> bool {
+        self.view_stack().is_empty()
+    }
+
+    pub fn eof(&self) -> bool {
+        self.index >= self.input.len()
+    }
 
-        // Underline location of error 
-        line.push('\n');
-        line.push('\t');
-        line.push_str(&" ".repeat(self.current.column()));
-        line.push_str(&"^");
-        line.push(' ');
-        line.push_str(&underline_msg);
+    /// Same as debug_line, but underlines the given location instead of the current location
+    pub fn debug_line_at(&self, location: Location, underline_msg: &str) -> String {
+        location.debug_line(self.input, underline_msg)
+    }
 
-        (self.current, line)
+    pub fn debug_line(&self, underline_msg: &str) -> (Location, String) {
+        (self.current, self.current.debug_line(self.input, underline_msg))
     }
-}
\ No newline at end of file
+}
@@ -1,16 +1,56 @@
+use crate::common::{
+    ExclaimError,
+    Location,
+};
+use crate::options::CompileOptions;
 use crate::tokens::*;
 
 pub mod automata;
 use automata::StackMachine;
 
+mod standalone;
+
 pub mod tests;
 
-pub fn run<S: AsRef<str>>(input: S) -> Result<Vec<Token>, String> {
+pub fn run(input: &str) -> Result<Vec<Token<'_>>, ExclaimError> {
+    run_with_options(input, &CompileOptions::default())
+}
+
+pub fn run_with_options<'a>(input: &'a str, options: &CompileOptions) -> Result<Vec<Token<'a>>, ExclaimError> {
+    validate_delimiters(options.open_delimiter(), options.close_delimiter())?;
+
+    let stack = StackMachine::new(input)
+        .with_delimiters(options.open_delimiter(), options.close_delimiter())
+        .with_normalized_line_endings(options.normalizes_line_endings());
+    let tokens = lex(stack)?;
+
+    if options.strips_standalone_lines() {
+        Ok(standalone::strip_standalone_lines(tokens, input))
+    } else {
+        Ok(tokens)
+    }
+}
+
+fn validate_delimiters(open: &str, close: &str) -> Result<(), ExclaimError> {
+    for delimiter in [open, close] {
+        if delimiter.is_empty() || delimiter.chars().any(|ch| ch.is_whitespace()) {
+            return Err(ExclaimError::Lex(
+                Location::default(), 
+                format!("Lexer: Invalid delimiter \"{}\". Delimiters can't be empty or contain whitespace.", delimiter),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn lex(mut stack: StackMachine<'_>) -> Result<Vec<Token<'_>>, ExclaimError> {
     let mut state = State::new();
-    let mut stack = StackMachine::new(input);
 
     while !stack.eof() {
-        if stack.peek() == '\n' {
+        if stack.at_dropped_carriage_return() {
+            stack.skip_current();
+        } else if stack.peek() == '\n' {
             state = state.run(&mut stack)?;
             stack.newline();
         } else {
@@ -18,123 +58,211 @@ pub fn run<S: AsRef<str>>(input: S) -> Result<Vec<Token>, String> {
         }
     }
 
+    // A string literal still open at the end of the input has no closing quote
+    if std::ptr::eq(state, &STATE_BLOCK_STRING_LITERAL) {
+        let location = stack.location();
+        return Err(State::error_at(&mut stack, location, "Lexer<STRING>: The string literal is never closed with '\"'.", "the string literal starts here"));
+    }
+
     // consume leftovers
     if !stack.empty() {
-        stack.accept_token(Token::StringLiteral(stack.view_stack().to_string(), stack.location()));
+        let text = stack.take_stack();
+        stack.accept_token(Token::StringLiteral(text, stack.span()));
     }
 
     Ok(stack.get_tokens())
 }
 
-struct State(fn(&mut StackMachine) -> Result<&'static State, String>);
+struct State(fn(&mut StackMachine) -> Result<&'static State, ExclaimError>);
 
 impl State {
     pub fn new() -> &'static State {
         &STATE_START
     }
 
-    pub fn run(&self, stack: &mut StackMachine) -> Result<&'static State, String> {
+    pub fn run(&self, stack: &mut StackMachine) -> Result<&'static State, ExclaimError> {
         self.0(stack)
     }
 
-    pub fn get_error_msg(stack: &mut StackMachine, msg: &str, underline_msg: &str) -> String {
+    pub fn error(stack: &mut StackMachine, msg: &str, underline_msg: &str) -> ExclaimError {
         let (loc, line) = stack.debug_line(underline_msg);
-        format!("{} On line [{}; {}]:\n\t{}", msg, loc.line(), loc.column(), line)
+        ExclaimError::Lex(loc, format!("{} On line [{}; {}]:\n\t{}", msg, loc.line(), loc.column(), line))
+    }
+
+    /// Same as error, but reported at the given location instead of the current location 
+    pub fn error_at(stack: &mut StackMachine, loc: Location, msg: &str, underline_msg: &str) -> ExclaimError {
+        let line = stack.debug_line_at(loc, underline_msg);
+        ExclaimError::Lex(loc, format!("{} On line [{}; {}]:\n\t{}", msg, loc.line(), loc.column(), line))
     }
 }
 
 static STATE_START: State = State(
     |stack| {
-        match stack.peek() {
-            '{' => Ok(&STATE_OPEN_BLOCK),
-            '}' => Ok(&STATE_CLOSE_BLOCK),
-            _ => {
-                stack.push();
-                Ok(&STATE_START)
-            }
+        // Outside of a code block the close delimiter '}}' is plain text, only the open delimiter '{{' opens a block 
+        if stack.at_block_open(0) {
+            Ok(&STATE_OPEN_BLOCK)
+        } else if stack.peek() == '\\' && stack.at_block_open(1) {
+            // Escaped open delimiter \{{ is written as {{ 
+            stack.skip_current();
+            stack.push_n(stack.open_len());
+            Ok(&STATE_START)
+        } else {
+            stack.push();
+            Ok(&STATE_START)
         }
     }
 );
 
 static STATE_OPEN_BLOCK: State = State(
     |stack| {
-        // Context, we already know stack.peek() == '{'
-        match stack.lookahead().unwrap_or(&' ') {
-            '{' => {
-                // Accept string literal if the stack is not empty, because next token is a BlockOpen 
-                if !stack.empty() {
-                    stack.accept_token(Token::StringLiteral(stack.view_stack().to_string(), stack.location()));
-                }
-                Ok(&ACCEPT_OPEN_BLOCK)
-            },
-            _ => {
-                stack.push();
-                Ok(&STATE_START)
-            }
+        // Context, the input continues with the open delimiter 
+        // Accept string literal if the stack is not empty, because next token is a BlockOpen 
+        if !stack.empty() {
+            let text = stack.take_stack();
+            stack.accept_token(Token::StringLiteral(text, stack.span()));
         }
-    }
-);
 
-static STATE_CLOSE_BLOCK: State = State(
-    |stack| {
-        // Context, we already know stack.peek() == '}'
-        match stack.lookahead().unwrap_or(&' ') {
-            '}' => {
-                // Accept string literal if the stack is not empty, because next token is a BlockClose 
-                if !stack.empty() {
-                    stack.accept_token(Token::StringLiteral(stack.view_stack().to_string(), stack.location()));
-                }
-                Ok(&ACCEPT_CLOSE_BLOCK)
-            },
-            _ => {
-                stack.push();
-                Ok(&STATE_START)
-            }
+        // Comments {{# ... #}} are discarded 
+        if stack.lookahead_nth(stack.open_len()) == Some('#') {
+            return Ok(&ACCEPT_OPEN_COMMENT);
+        }
+
+        // Trim marker {{- strips the whitespace at the end of the preceding text 
+        if stack.lookahead_nth(stack.open_len()) == Some('-') {
+            stack.trim_last_text();
         }
+        Ok(&ACCEPT_OPEN_BLOCK)
     }
 );
 
 static ACCEPT_OPEN_BLOCK: State = State(
     |stack| {
-        stack.push(); // {
-        stack.push(); // {{
-        stack.accept_token(Token::Operator(Op::BlockOpen, stack.location()));
+        stack.push_n(stack.open_len()); // {{
+        stack.accept_token(Token::Operator(Op::BlockOpen, stack.span()));
+
+        // Trim marker {{-, the preceding text was trimmed before the BlockOpen was accepted 
+        if !stack.eof() && stack.peek() == '-' {
+            stack.skip();
+        }
+
         Ok(&STATE_BLOCK)
     }
 );
 
 static ACCEPT_CLOSE_BLOCK: State = State(
     |stack| {
-        stack.push(); // }
-        stack.push(); // }}
-        stack.accept_token(Token::Operator(Op::BlockClose, stack.location()));
-        Ok(&STATE_START)
+        stack.push_n(stack.close_len()); // }}
+        stack.accept_token(Token::Operator(Op::BlockClose, stack.span()));
+
+        // Trim marker -}} strips the whitespace at the start of the following text 
+        if stack.take_trim_next() {
+            Ok(&STATE_TRIM_WHITESPACE)
+        } else {
+            Ok(&STATE_START)
+        }
+    }
+);
+
+static ACCEPT_OPEN_COMMENT: State = State(
+    |stack| {
+        let comment_close = format!("#{}", stack.close_delimiter());
+        if !stack.ahead_contains(stack.open_len() + 1, &comment_close) {
+            return Err(State::error(
+                stack, 
+                "Lexer<COMMENT>: The comment is never closed.",
+                &format!("expected '{}' to close the comment", comment_close),
+            ));
+        }
+
+        stack.skip_n(stack.open_len() + 1); // {{#
+        Ok(&STATE_COMMENT)
+    }
+);
+
+static STATE_COMMENT: State = State(
+    |stack| {
+        if stack.peek() == '#' && stack.at_block_close(1) {
+            stack.skip_n(1 + stack.close_len()); // #}}
+            Ok(&STATE_START)
+        } else {
+            // Everything inside of the comment is skipped, including '{{' and '}}' 
+            stack.skip();
+            Ok(&STATE_COMMENT)
+        }
+    }
+);
+
+static STATE_TRIM_WHITESPACE: State = State(
+    |stack| {
+        if stack.peek().is_whitespace() {
+            stack.skip();
+            Ok(&STATE_TRIM_WHITESPACE)
+        } else {
+            Ok(&STATE_START)
+        }
     }
 );
 
 static STATE_BLOCK: State = State(
     |stack| {
+        // The close delimiter is matched first, in case it starts with an operator
+        if stack.at_block_close(0) {
+            return Ok(&STATE_CLOSE_BLOCK_FROM_BLOCK);
+        } else if stack.at_block_open(0) {
+            return Ok(&STATE_OPEN_BLOCK_FROM_BLOCK);
+        }
+
         let ch = stack.peek();
         match ch {
-            '{' => Ok(&STATE_OPEN_BLOCK_FROM_BLOCK),
-            '}' => Ok(&STATE_CLOSE_BLOCK_FROM_BLOCK),
+            '{' => {
+                stack.push();
+                stack.accept_token(Token::Operator(Op::BlockOpenPrime, stack.span()));
+                Ok(&STATE_BLOCK)
+            },
+            '}' => {
+                stack.push();
+                stack.accept_token(Token::Operator(Op::BlockClosePrime, stack.span()));
+                Ok(&STATE_BLOCK)
+            },
             '!' => Ok(&STATE_BLOCK_ACTION_INEQUALITY),
             '=' => Ok(&STATE_BLOCK_ASSIGN_EQUALITY),
+            '<' => Ok(&STATE_BLOCK_LESS),
+            '+' => {
+                stack.push();
+                stack.accept_token(Token::Operator(Op::Add, stack.span()));
+                Ok(&STATE_BLOCK)
+            },
+            '*' => {
+                stack.push();
+                stack.accept_token(Token::Operator(Op::Multiply, stack.span()));
+                Ok(&STATE_BLOCK)
+            },
+            '/' => {
+                stack.push();
+                stack.accept_token(Token::Operator(Op::Divide, stack.span()));
+                Ok(&STATE_BLOCK)
+            },
+            '%' => {
+                stack.push();
+                stack.accept_token(Token::Operator(Op::Remainder, stack.span()));
+                Ok(&STATE_BLOCK)
+            },
+            '>' => Ok(&STATE_BLOCK_GREATER),
             '|' => Ok(&STATE_BLOCK_PIPE_OR),
             '&' => Ok(&STATE_BLOCK_AND),
             ',' => {
                 stack.push();
-                stack.accept_token(Token::Operator(Op::Comma, stack.location()));
+                stack.accept_token(Token::Operator(Op::Comma, stack.span()));
                 Ok(&STATE_BLOCK)
             }
             '.' => {
                 stack.push();
-                stack.accept_token(Token::Operator(Op::Dot, stack.location()));
+                stack.accept_token(Token::Operator(Op::Dot, stack.span()));
                 Ok(&STATE_BLOCK)
             },
             ':' => {
                 stack.push();
-                stack.accept_token(Token::Operator(Op::Each, stack.location()));
+                stack.accept_token(Token::Operator(Op::Each, stack.span()));
                 Ok(&STATE_BLOCK)
             },
             '"' => {
@@ -143,44 +271,52 @@ static STATE_BLOCK: State = State(
             },
             '[' => {
                 stack.push();
-                stack.accept_token(Token::Operator(Op::ClosureOpen, stack.location()));
+                stack.accept_token(Token::Operator(Op::ClosureOpen, stack.span()));
                 Ok(&STATE_BLOCK)
             },
             ']' => {
                 stack.push();
-                stack.accept_token(Token::Operator(Op::ClosureClose, stack.location()));
+                stack.accept_token(Token::Operator(Op::ClosureClose, stack.span()));
                 Ok(&STATE_BLOCK)
             },
             '(' => {
                 stack.push();
-                stack.accept_token(Token::Operator(Op::ParenOpen, stack.location()));
+                stack.accept_token(Token::Operator(Op::ParenOpen, stack.span()));
                 Ok(&STATE_BLOCK)
             },
             ')' => {
                 stack.push();
-                stack.accept_token(Token::Operator(Op::ParenClose, stack.location()));
+                stack.accept_token(Token::Operator(Op::ParenClose, stack.span()));
                 Ok(&STATE_BLOCK)
             },
             '-' => {
-                stack.push();
-                Ok(&STATE_INT)
+                if stack.at_block_close(1) {
+                    // Trim marker -}}
+                    stack.skip();
+                    stack.set_trim_next();
+                } else {
+                    // Subtraction, or negation when the parser finds it in front of an operand
+                    stack.push();
+                    stack.accept_token(Token::Operator(Op::Subtract, stack.span()));
+                }
+                Ok(&STATE_BLOCK)
             }
             _ => {
-                if ch.is_alphabetic() {
+                if is_label_start(ch) {
                     stack.push();
                     Ok(&STATE_LABEL_ACTION)
-                } else if ch.is_numeric() {
+                } else if ch.is_ascii_digit() {
                     stack.push();
-                    Ok(&STATE_UINT)
+                    Ok(&STATE_NUMBER)
                 } else if ch.is_whitespace() {
                     stack.skip();
                     Ok(&STATE_BLOCK)
                 } else {
-                    panic!(State::get_error_msg(
+                    Err(State::error(
                         stack, 
                         &format!("Lexer<BLOCK>: Encountered unknown character '{}'.", ch),
                         "unknown character",
-                    ));
+                    ))
                 }
             }
         }
@@ -189,54 +325,45 @@ static STATE_BLOCK: State = State(
 
 static STATE_OPEN_BLOCK_FROM_BLOCK: State = State(
     |stack| {
-        // Context, we already know stack.peek() == '{'
-        match stack.lookahead().unwrap_or(&' ') {
-            '{' => {
-                // Accept string literal if the stack is not empty, because next token is a BlockOpen 
-                if !stack.empty() {
-                    stack.accept_token(Token::StringLiteral(stack.view_stack().to_string(), stack.location()));
-                }
-                Ok(&ACCEPT_OPEN_BLOCK)
-            },
-            _ => {
-                stack.push();
-                Ok(&STATE_BLOCK)
-            }
+        // Context, the input continues with the open delimiter 
+        // Accept string literal if the stack is not empty, because next token is a BlockOpen 
+        if !stack.empty() {
+            let text = stack.take_stack();
+            stack.accept_token(Token::StringLiteral(text, stack.span()));
         }
+        Ok(&ACCEPT_OPEN_BLOCK)
     }
 );
 
 static STATE_CLOSE_BLOCK_FROM_BLOCK: State = State(
     |stack| {
-        // Context, we already know stack.peek() == '}'
-        match stack.lookahead().unwrap_or(&' ') {
-            '}' => {
-                // Accept string literal if the stack is not empty, because next token is a BlockClose 
-                if !stack.empty() {
-                    stack.accept_token(Token::StringLiteral(stack.view_stack().to_string(), stack.location()));
-                }
-                Ok(&ACCEPT_CLOSE_BLOCK)
-            },
-            _ => {
-                stack.push();
-                Ok(&STATE_BLOCK)
-            }
+        // Context, the input continues with the close delimiter 
+        // Accept string literal if the stack is not empty, because next token is a BlockClose 
+        if !stack.empty() {
+            let text = stack.take_stack();
+            stack.accept_token(Token::StringLiteral(text, stack.span()));
         }
+        Ok(&ACCEPT_CLOSE_BLOCK)
     }
 );
 
 static STATE_BLOCK_ACTION_INEQUALITY: State = State(
     |stack| {
         // Context, we already know stack.peek() == '!'
-        match stack.lookahead().unwrap_or(&' ') {
+        match stack.lookahead().unwrap_or(' ') {
             '=' => {
                 stack.push(); // !
                 stack.push(); // !=
-                stack.accept_token(Token::Operator(Op::Inequality, stack.location()));
+                stack.accept_token(Token::Operator(Op::Inequality, stack.span()));
             }
             _ => {
                 stack.push();
-                stack.accept_token(Token::Action(Action::End, stack.location()));
+                // The end action is the only action without a label, it directly follows the open delimiter 
+                if matches!(stack.last_token(), Some(Token::Operator(Op::BlockOpen, _))) {
+                    stack.accept_token(Token::Action(Action::End, stack.span()));
+                } else {
+                    stack.accept_token(Token::Operator(Op::Not, stack.span()));
+                }
             }
         }
 
@@ -248,15 +375,53 @@ static STATE_BLOCK_ACTION_INEQUALITY: State = State(
 static STATE_BLOCK_ASSIGN_EQUALITY: State = State(
     |stack| {
         // Context, we already know stack.peek() == '='
-        match stack.lookahead().unwrap_or(&' ') {
+        match stack.lookahead().unwrap_or(' ') {
             '=' => {
                 stack.push(); // =
                 stack.push(); // ==
-                stack.accept_token(Token::Operator(Op::Equality, stack.location()));
+                stack.accept_token(Token::Operator(Op::Equality, stack.span()));
             }
             _ => {
                 stack.push(); // =
-                stack.accept_token(Token::Operator(Op::Assign, stack.location()));
+                stack.accept_token(Token::Operator(Op::Assign, stack.span()));
+            }
+        }
+
+        Ok(&STATE_BLOCK)
+    }
+);
+
+static STATE_BLOCK_LESS: State = State(
+    |stack| {
+        // Context, we already know stack.peek() == '<'
+        match stack.lookahead().unwrap_or(' ') {
+            '=' => {
+                stack.push(); // <
+                stack.push(); // <=
+                stack.accept_token(Token::Operator(Op::LessEqual, stack.span()));
+            }
+            _ => {
+                stack.push(); // <
+                stack.accept_token(Token::Operator(Op::Less, stack.span()));
+            }
+        }
+
+        Ok(&STATE_BLOCK)
+    }
+);
+
+static STATE_BLOCK_GREATER: State = State(
+    |stack| {
+        // Context, we already know stack.peek() == '>'
+        match stack.lookahead().unwrap_or(' ') {
+            '=' => {
+                stack.push(); // >
+                stack.push(); // >=
+                stack.accept_token(Token::Operator(Op::GreaterEqual, stack.span()));
+            }
+            _ => {
+                stack.push(); // >
+                stack.accept_token(Token::Operator(Op::Greater, stack.span()));
             }
         }
 
@@ -269,12 +434,34 @@ static STATE_BLOCK_STRING_LITERAL: State = State(
         match stack.peek() {
             '"' => { 
                 stack.skip_current();   // Skip closing double quote
-                stack.accept_token(Token::StringLiteral(stack.view_stack().to_string(), stack.location()));
+                let text = stack.take_stack();
+                stack.accept_token(Token::StringLiteral(text, stack.span()));
                 Ok(&STATE_BLOCK)
             },
             '\\' => { // ESCAPE CHARACTER
-                stack.skip_current();   // Skip escape
-                stack.push();           // Push character escaped
+                let (escaped, len) = match stack.lookahead() {
+                    Some('n') => ('\n', 2),
+                    Some('t') => ('\t', 2),
+                    Some('r') => ('\r', 2),
+                    Some('\\') => ('\\', 2),
+                    Some('"') => ('"', 2),
+                    Some('0') => ('\0', 2),
+                    Some('u') => unicode_escape(stack)?,
+                    Some(ch) => {
+                        return Err(State::error(
+                            stack, 
+                            &format!("Lexer<STRING>: Unknown escape sequence '\\{}' in string literal.", ch),
+                            "expected one of the escape sequences: \\n, \\t, \\r, \\\\, \\\", \\0 or \\u{XXXX}",
+                        ))
+                    },
+                    None => return Err(State::error(
+                        stack, 
+                        "Lexer<STRING>: The string literal is never closed with '\"'.",
+                        "expected an escape sequence",
+                    )),
+                };
+
+                stack.push_escaped(escaped, len);
                 Ok(&STATE_BLOCK_STRING_LITERAL)
             },
             _ => {
@@ -285,22 +472,60 @@ static STATE_BLOCK_STRING_LITERAL: State = State(
     }
 );
 
+/// Reads the unicode escape \u{XXXX} at the current character, returns the escaped character and the length of the escape sequence
+fn unicode_escape(stack: &mut StackMachine) -> Result<(char, usize), ExclaimError> {
+    if stack.lookahead_nth(2) != Some('{') {
+        return Err(State::error(
+            stack, 
+            "Lexer<STRING>: Unicode escape sequences must be written with braces.",
+            "expected \\u{XXXX}",
+        ));
+    }
+
+    // Up to 6 hexadecimal digits between the braces
+    let mut digits = String::new();
+    let mut len = 3;
+    loop {
+        match stack.lookahead_nth(len) {
+            Some('}') if !digits.is_empty() => break,
+            Some(ch) if ch.is_ascii_hexdigit() && digits.len() < 6 => digits.push(ch),
+            _ => return Err(State::error(
+                stack, 
+                "Lexer<STRING>: Invalid unicode escape sequence, expected 1 to 6 hexadecimal digits closed by '}'.",
+                "expected \\u{XXXX}",
+            )),
+        }
+        len += 1;
+    }
+
+    // Digits are valid hexadecimal, so only the value can be invalid 
+    let value = u32::from_str_radix(&digits, 16).unwrap_or(u32::MAX);
+    match char::from_u32(value) {
+        Some(ch) => Ok((ch, len + 1)),
+        None => Err(State::error(
+            stack, 
+            &format!("Lexer<STRING>: Invalid unicode escape sequence, '{}' is not a unicode character.", digits),
+            "expected a unicode scalar value",
+        )),
+    }
+}
+
 static STATE_BLOCK_AND: State = State(
     |stack| {
         // Context, we already know stack.peek() == '&'
-        match stack.lookahead().unwrap_or(&' ') {
+        match stack.lookahead().unwrap_or(' ') {
             '&' => {
                 stack.push(); // &
                 stack.push(); // &&
-                stack.accept_token(Token::Operator(Op::And, stack.location()));
+                stack.accept_token(Token::Operator(Op::And, stack.span()));
                 Ok(&STATE_BLOCK)
             }
             _ => {
-                panic!(State::get_error_msg(
+                Err(State::error(
                     stack, 
                     "Lexer<AND>: Expected Operator And(&&). A single '&' is not a valid token.",
                     "expected '&&'",
-                ));
+                ))
             }
         }
     }
@@ -309,28 +534,62 @@ static STATE_BLOCK_AND: State = State(
 static STATE_BLOCK_PIPE_OR: State = State(
     |stack| {
         // Context, we already know stack.peek() == '|'
-        match stack.lookahead().unwrap_or(&' ') {
+        match stack.lookahead().unwrap_or(' ') {
             '|' => {
                 stack.push(); // |
                 stack.push(); // || Or
-                stack.accept_token(Token::Operator(Op::Or, stack.location()));
+                stack.accept_token(Token::Operator(Op::Or, stack.span()));
             }
             _ => {
                 stack.push(); // | Pipe
-                stack.accept_token(Token::Operator(Op::Pipe, stack.location()));
+                stack.accept_token(Token::Operator(Op::Pipe, stack.span()));
             }
         }
         Ok(&STATE_BLOCK)
     }
 );
 
+/// (trim_before: bool, trim_after: bool) 
+/// 
+/// Matches the block closing a raw! region at the start of text: {{!}}, {{ ! }}, {{-!-}}. The flags are set by its trim markers.
+fn raw_close(text: &str, open: &str, close: &str) -> Option<(bool, bool)> {
+    let rest = text.strip_prefix(open)?;
+    let (trim_before, rest) = match rest.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+
+    let rest = rest.trim_start().strip_prefix('!')?.trim_start();
+    let (trim_after, rest) = match rest.strip_prefix('-') {
+        Some(rest) if rest.starts_with(close) => (true, rest),
+        _ => (false, rest),
+    };
+
+    rest.starts_with(close).then_some((trim_before, trim_after))
+}
+
+/// Returns true if a block closing a raw! region starts anywhere in text
+fn contains_raw_close(text: &str, open: &str, close: &str) -> bool {
+    text.match_indices(open).any(|(index, _)| raw_close(&text[index..], open, close).is_some())
+}
+
+/// Labels start with a letter or an underscore, similar to XID_Start 
+fn is_label_start(ch: char) -> bool {
+    ch.is_alphabetic() || ch == '_'
+}
+
+/// After the first character, labels can also contain digits, similar to XID_Continue 
+fn is_label_continue(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
 static STATE_LABEL_ACTION: State = State(
     |stack| {
         let ch = stack.peek();
-        if ch.is_alphabetic() || ch == '_' {
+        if is_label_continue(ch) {
             stack.push();
             Ok(&STATE_LABEL_ACTION)
-        } else if ch == '!' && *stack.lookahead().unwrap_or(&' ') != '=' {
+        } else if ch == '!' && stack.lookahead().unwrap_or(' ') != '=' {
             // If the following two characters are not: !=
             // Push ! 
             stack.push();
@@ -341,97 +600,254 @@ static STATE_LABEL_ACTION: State = State(
                 "let!" => Action::Let,
                 "write!" => Action::Write,
                 "render!" => Action::Render,
-                _ => panic!(State::get_error_msg(
+                "include!" => Action::Include,
+                "extends!" => Action::Extends,
+                "block!" => Action::Block,
+                "super!" => Action::Super,
+                "if!" => Action::If,
+                "elif!" => Action::Elif,
+                "else!" => Action::Else,
+                // raw! does not produce a token, its region is passed through as text 
+                "raw!" => return Ok(&ACCEPT_RAW),
+                _ => return Err(State::error(
                                 stack, 
                                 &format!("Lexer<LABEL>: The expected action does not match any defined action - invalid action found: '{}' ", stack.view_stack()), 
-                                "expected one of the following defined actions: let!, write!, render!, or !."))
+                                "expected one of the following defined actions: let!, write!, render!, include!, extends!, block!, super!, if!, elif!, else!, raw!, or !."))
             };
 
-            stack.accept_token(Token::Action(action, stack.location()));
+            stack.accept_token(Token::Action(action, stack.span()));
             Ok(&STATE_BLOCK)
 
-        } else if ch.is_numeric() {
-            panic!(State::get_error_msg(
-                stack, 
-                &format!("Lexer<LABEL>: The expected label contains digit '{}' with stack \"{}\".", ch, stack.view_stack()),
-                "expected alphabetic character",
-            ));
         } else {
-            // Accept Label 
-            stack.accept_token(Token::Label(stack.view_stack().to_string(), stack.location()));
+            // Accept Label, unless it is a reserved literal
+            let token = match stack.view_stack() {
+                "true" => Token::BoolLiteral(true, stack.span()),
+                "false" => Token::BoolLiteral(false, stack.span()),
+                "null" => Token::NullLiteral(stack.span()),
+                _ => Token::Label(stack.take_stack(), stack.span()),
+            };
+            stack.accept_token(token);
             Ok(&STATE_BLOCK)
         }
     }
 );
 
-static STATE_UINT: State = State(
+static ACCEPT_RAW: State = State(
     |stack| {
-        let ch = stack.peek();
-        if ch.is_numeric() {
-            stack.push();
-            Ok(&STATE_UINT)
-        } else if ch == '.' {
-            stack.push();
-            Ok(&STATE_FLOAT)
-        } else if ch.is_alphabetic() {
-            panic!(State::get_error_msg(
+        // Context, the stack holds "raw!"
+        if !matches!(stack.last_token(), Some(Token::Operator(Op::BlockOpen, _))) {
+            return Err(State::error(
                 stack, 
-                &format!("Lexer<DIGIT>: The expected number contains invalid digit '{}' with stack \"{}\".", ch, stack.view_stack()),
-                "expected digit",
+                "Lexer<RAW>: raw! can only be used at the start of a block.",
+                &format!("expected raw! right after '{}'", stack.open_delimiter()),
             ));
-        } else {
-            // Accept Number 
-            let number: usize = stack.view_stack().parse::<usize>().unwrap();
-            let token = Token::NumberLiteral(Number::Uint(number), stack.location());
-            stack.accept_token(token);
-            Ok(&STATE_BLOCK)
         }
+
+        let _block_open = stack.pop_token();
+        stack.discard_stack();
+        Ok(&STATE_RAW_OPEN)
     }
 );
 
-static STATE_INT: State = State(
+static STATE_RAW_OPEN: State = State(
     |stack| {
         let ch = stack.peek();
-        if ch.is_numeric() {
-            stack.push();
-            Ok(&STATE_INT)
-        } else if ch == '.' {
-            stack.push();
-            Ok(&STATE_FLOAT)
-        } else if ch.is_alphabetic() {
-            panic!(State::get_error_msg(
+        if ch.is_whitespace() {
+            stack.skip();
+            Ok(&STATE_RAW_OPEN)
+        } else if ch == '-' && stack.at_block_close(1) {
+            // Trim marker -}} strips the whitespace at the start of the region 
+            stack.skip();
+            stack.set_trim_next();
+            Ok(&STATE_RAW_OPEN)
+        } else if stack.at_block_close(0) {
+            if !contains_raw_close(stack.ahead(stack.close_len()), stack.open_delimiter(), stack.close_delimiter()) {
+                let raw_close = format!("{}!{}", stack.open_delimiter(), stack.close_delimiter());
+                return Err(State::error(
+                    stack, 
+                    "Lexer<RAW>: The raw! block is never closed.",
+                    &format!("expected '{}' to close the raw! block", raw_close),
+                ));
+            }
+
+            stack.skip_n(stack.close_len()); // }}
+            if stack.take_trim_next() {
+                Ok(&STATE_RAW_TRIM_WHITESPACE)
+            } else {
+                Ok(&STATE_RAW)
+            }
+        } else {
+            let close = stack.close_delimiter().to_string();
+            Err(State::error(
                 stack, 
-                &format!("Lexer<DIGIT>: The expected number contains invalid digit '{}' with stack \"{}\".", ch, stack.view_stack()),
-                "expected digit",
-            ));
+                &format!("Lexer<RAW>: Expected '{}' after raw!, but found '{}'.", close, ch),
+                &format!("expected '{}'", close),
+            ))
+        }
+    }
+);
+
+static STATE_RAW_TRIM_WHITESPACE: State = State(
+    |stack| {
+        if stack.peek().is_whitespace() {
+            stack.skip();
+            Ok(&STATE_RAW_TRIM_WHITESPACE)
         } else {
-            // Accept Number 
-            let number: isize = stack.view_stack().parse::<isize>().unwrap();
-            let token = Token::NumberLiteral(Number::Int(number), stack.location());
-            stack.accept_token(token);
-            Ok(&STATE_BLOCK)
+            Ok(&STATE_RAW)
+        }
+    }
+);
+
+static STATE_RAW: State = State(
+    |stack| {
+        // Context, the region ends with {{!}}, which can have whitespace and trim markers like any closing block: {{- ! -}} 
+        match raw_close(stack.ahead(0), stack.open_delimiter(), stack.close_delimiter()) {
+            Some((trim_before, trim_after)) => {
+                // Accept the content of the region as is 
+                if !stack.empty() {
+                    let text = stack.take_stack();
+                    stack.accept_token(Token::StringLiteral(text, stack.span()));
+                    if trim_before {
+                        stack.trim_last_text();
+                    }
+                }
+                if trim_after {
+                    stack.set_trim_next();
+                }
+
+                stack.skip_n(stack.open_len()); // {{
+                Ok(&STATE_RAW_CLOSE)
+            },
+            None => {
+                stack.push();
+                Ok(&STATE_RAW)
+            },
         }
     }
 );
 
-static STATE_FLOAT: State = State(
+static STATE_RAW_CLOSE: State = State(
     |stack| {
+        // Context, raw_close() matched the rest of the closing block: whitespace, trim markers, '!' and '}}' 
+        if stack.at_block_close(0) {
+            stack.skip_n(stack.close_len()); // }}
+            if stack.take_trim_next() {
+                Ok(&STATE_TRIM_WHITESPACE)
+            } else {
+                Ok(&STATE_START)
+            }
+        } else {
+            stack.skip();
+            Ok(&STATE_RAW_CLOSE)
+        }
+    }
+);
+
+static STATE_NUMBER: State = State(
+    |stack| {
+        // Collect everything that could belong to the number, it is validated once complete
         let ch = stack.peek();
-        if ch.is_numeric() {
+        let exponent_sign = (ch == '+' || ch == '-') 
+            && stack.view_stack().ends_with(['e', 'E']) 
+            && !stack.view_stack().contains(['x', 'X']);
+
+        if ch.is_alphanumeric() || ch == '_' || ch == '.' || exponent_sign {
             stack.push();
-            Ok(&STATE_FLOAT)
-        } else if ch.is_alphabetic() {
-            panic!(State::get_error_msg(
-                stack, 
-                &format!("Lexer<DIGIT>: The expected number contains invalid digit '{}' with stack \"{}\".", ch, stack.view_stack()),
-                "expected digit",
-            ));
+            Ok(&STATE_NUMBER)
         } else {
             // Accept Number 
-            let number: f64 = stack.view_stack().parse::<f64>().unwrap();
-            let token = Token::NumberLiteral(Number::Float(number), stack.location());
-            stack.accept_token(token);
-            Ok(&STATE_BLOCK)
+            match parse_number(stack.view_stack()) {
+                Ok(number) => {
+                    let token = Token::NumberLiteral(number, stack.span());
+                    stack.accept_token(token);
+                    Ok(&STATE_BLOCK)
+                },
+                Err((offset, msg, underline_msg)) => {
+                    let location = stack.location();
+                    let location = Location::with_offset(location.line(), location.column() + offset, location.offset() + offset);
+                    Err(State::error_at(stack, location, &msg, underline_msg))
+                },
+            }
         }
     }
-);
\ No newline at end of file
+);
+
+/// (offset: usize, message: String, underline: &str) 
+/// 
+/// offset is the position of the offending character in the number
+type NumberError = (usize, String, &'static str);
+
+/// Parses the literal forms of numbers: 42, 4.2, 4.2e-1, 1e6, 0xFF, 1_000_000. A leading '-' is lexed as an operator.
+fn parse_number(lexeme: &str) -> Result<Number, NumberError> {
+    let hexadecimal = lexeme.starts_with("0x") || lexeme.starts_with("0X");
+    let prefix_len = if hexadecimal { 2 } else { 0 };
+    let body = &lexeme[prefix_len..];
+    let is_digit = |ch: char| if hexadecimal { ch.is_ascii_hexdigit() } else { ch.is_ascii_digit() };
+
+    // Validate every character, and that the separators are placed between digits 
+    let chars: Vec<char> = body.chars().collect();
+    let mut seen_dot = false;
+    let mut seen_exponent = false;
+    for (index, ch) in chars.iter().enumerate() {
+        let offset = prefix_len + index;
+        let previous = if index > 0 { chars.get(index - 1).copied() } else { None };
+        let next = chars.get(index + 1).copied();
+
+        match ch {
+            '_' => {
+                if !previous.map(is_digit).unwrap_or_default() || !next.map(is_digit).unwrap_or_default() {
+                    return Err((offset, format!("Lexer<NUMBER>: Malformed number \"{}\", '_' can only separate digits.", lexeme), "expected a digit on both sides of '_'"));
+                }
+            },
+            '.' if !hexadecimal => {
+                if seen_dot || seen_exponent {
+                    return Err((offset, format!("Lexer<NUMBER>: Malformed number \"{}\", unexpected '.'.", lexeme), "unexpected '.'"));
+                }
+                if !next.map(is_digit).unwrap_or_default() {
+                    return Err((offset, format!("Lexer<NUMBER>: Malformed number \"{}\", expected a digit after '.'.", lexeme), "expected a digit after '.'"));
+                }
+                seen_dot = true;
+            },
+            'e' | 'E' if !hexadecimal => {
+                if seen_exponent {
+                    return Err((offset, format!("Lexer<NUMBER>: Malformed number \"{}\", unexpected exponent.", lexeme), "unexpected exponent"));
+                }
+                let exponent_digit = match next {
+                    Some('+') | Some('-') => chars.get(index + 2).copied(),
+                    next => next,
+                };
+                if !exponent_digit.map(is_digit).unwrap_or_default() {
+                    return Err((offset, format!("Lexer<NUMBER>: Malformed number \"{}\", expected digits in the exponent.", lexeme), "expected digits after the exponent"));
+                }
+                seen_exponent = true;
+            },
+            '+' | '-' if matches!(previous, Some('e') | Some('E')) => (),
+            ch if is_digit(*ch) => (),
+            ch => {
+                let expected = if hexadecimal { "expected hexadecimal digit" } else { "expected digit" };
+                return Err((offset, format!("Lexer<NUMBER>: The number \"{}\" contains invalid digit '{}'.", lexeme, ch), expected));
+            },
+        }
+    }
+
+    if body.is_empty() {
+        return Err((prefix_len, format!("Lexer<NUMBER>: Malformed number \"{}\", expected hexadecimal digits after '0x'.", lexeme), "expected hexadecimal digit"));
+    }
+
+    let body = body.replace('_', "");
+    let overflow = |kind: &str| (0, format!("Lexer<NUMBER>: The number \"{}\" does not fit in {}.", lexeme, kind), "number is out of range");
+
+    if seen_dot || seen_exponent {
+        // The format was validated, only the range can be invalid 
+        let float = body.parse::<f64>().map_err(|_| overflow("a float"))?;
+        if float.is_infinite() {
+            return Err(overflow("a float"));
+        }
+        return Ok(Number::Float(float));
+    }
+
+    let radix = if hexadecimal { 16 } else { 10 };
+    usize::from_str_radix(&body, radix)
+        .map(Number::Uint)
+        .map_err(|_| overflow("an unsigned integer"))
+}
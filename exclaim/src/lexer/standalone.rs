@@ -0,0 +1,167 @@
+use std::borrow::Cow;
+
+use crate::common::{
+    Location,
+    Span,
+};
+use crate::tokens::*;
+
+/// Removes the lines holding nothing but a let!, render!, if!, elif!, else!, block!, extends! or {{!}} block, a comment, 
+/// or the delimiters of a raw! region. write!, include! and super! render output, so their lines are kept.
+/// The indentation before the block and the newline after it are cut from the surrounding text tokens.
+pub fn strip_standalone_lines<'a>(tokens: Vec<Token<'a>>, input: &str) -> Vec<Token<'a>> {
+    // Bytes to cut at the (start, end) of every text token
+    let mut cuts: Vec<(usize, usize)> = tokens.iter()
+        .map(|token| (0, token.string_literal().map(|text| text.len()).unwrap_or_default()))
+        .collect();
+
+    // Standalone lines are found on the original tokens, so two standalone lines can share the text between them
+    for (before, after) in standalone_candidates(&tokens, input) {
+        let line_start = match before {
+            None => Some(None),
+            Some(before) => text(&tokens[before]).and_then(|text| indentation_start(text, before == 0)).map(Some),
+        };
+        let line_end = match after {
+            None => Some(None),
+            Some(after) => text(&tokens[after]).and_then(|text| line_break_end(text, after + 1 == tokens.len())).map(Some),
+        };
+
+        if let (Some(line_start), Some(line_end)) = (line_start, line_end) {
+            if let (Some(before), Some(line_start)) = (before, line_start) {
+                cuts[before].1 = line_start;
+            }
+            if let (Some(after), Some(line_end)) = (after, line_end) {
+                cuts[after].0 = line_end;
+            }
+        }
+    }
+
+    tokens.into_iter()
+        .zip(cuts)
+        .filter_map(|(token, (start, end))| match token {
+            Token::StringLiteral(text, span) if (start, end) != (0, text.len()) => {
+                if start >= end {
+                    return None;
+                }
+
+                // The cut at the start is whitespace up to the first newline, the cut at the end is indentation. Neither is escaped,
+                // but a dropped '\r' makes the text shorter than the input, so the start is found in the input.
+                let span_start = span.start();
+                let span_end = span.end();
+                let cut_end = &text[end..];
+                let span = Span::new(
+                    if start > 0 {
+                        let newline = input[span_start.offset()..].find('\n').unwrap_or_default();
+                        Location::with_offset(span_start.line() + 1, 0, span_start.offset() + newline + 1)
+                    } else {
+                        span_start
+                    },
+                    Location::with_offset(span_end.line(), span_end.column() - cut_end.chars().count(), span_end.offset() - cut_end.len()),
+                );
+                let text = match text {
+                    Cow::Borrowed(text) => Cow::Borrowed(&text[start..end]),
+                    Cow::Owned(text) => Cow::Owned(text[start..end].to_string()),
+                };
+                Some(Token::StringLiteral(text, span))
+            },
+            token => Some(token),
+        })
+        .collect()
+}
+
+/// The (before, after) neighbours of everything that can be alone on its line, None is the start or the end of the input.
+fn standalone_candidates(tokens: &[Token], input: &str) -> Vec<(Option<usize>, Option<usize>)> {
+    let mut candidates = vec![];
+
+    let mut index = 0;
+    while index < tokens.len() {
+        match block_close(tokens, index) {
+            Some(close) => {
+                if tokens.get(index + 1).map(is_standalone_block).unwrap_or_default() {
+                    let before = if index == 0 { None } else { Some(index - 1) };
+                    let after = if close + 1 < tokens.len() { Some(close + 1) } else { None };
+                    candidates.push((before, after));
+                }
+                index = close + 1;
+            },
+            None => index += 1,
+        }
+    }
+
+    // Comments and raw! delimiters are dropped by the lexer, they are the gaps in the input next to text tokens
+    let input_start = if input.starts_with('\u{FEFF}') { '\u{FEFF}'.len_utf8() } else { 0 };
+    let is_text = |index: usize| text(&tokens[index]).is_some();
+    if let (Some(first), Some(last)) = (tokens.first(), tokens.last()) {
+        if is_text(0) && first.span().start().offset() > input_start {
+            candidates.push((None, Some(0)));
+        }
+        if is_text(tokens.len() - 1) && last.span().end().offset() < input.len() {
+            candidates.push((Some(tokens.len() - 1), None));
+        }
+    }
+    for index in 1..tokens.len() {
+        if is_text(index - 1) && is_text(index) && tokens[index - 1].span().end().offset() < tokens[index].span().start().offset() {
+            candidates.push((Some(index - 1), Some(index)));
+        }
+    }
+
+    candidates
+}
+
+/// Index of the BlockClose ending the block opened at index
+fn block_close(tokens: &[Token], index: usize) -> Option<usize> {
+    if let Token::Operator(Op::BlockOpen, _) = tokens[index] {
+        tokens.iter()
+            .skip(index)
+            .position(|token| matches!(token, Token::Operator(Op::BlockClose, _)))
+            .map(|offset| index + offset)
+    } else {
+        None
+    }
+}
+
+fn is_standalone_block(action: &Token) -> bool {
+    matches!(
+        action, 
+        Token::Action(Action::Let, _) | Token::Action(Action::Render, _) | Token::Action(Action::End, _) 
+        | Token::Action(Action::If, _) | Token::Action(Action::Elif, _) | Token::Action(Action::Else, _)
+        | Token::Action(Action::Block, _) | Token::Action(Action::Extends, _)
+    )
+}
+
+/// Text tokens are the string literals outside of blocks. Tokens next to a block are either text or other blocks.
+fn text<'a>(token: &'a Token<'_>) -> Option<&'a str> {
+    token.string_literal()
+}
+
+/// Start of the indentation at the end of the text, if the text ends with a newline followed by indentation. 
+/// Text at the start of the file doesn't need the newline.
+fn indentation_start(text: &str, start_of_file: bool) -> Option<usize> {
+    let line_start = match text.rfind('\n') {
+        Some(newline) => newline + 1,
+        None if start_of_file => 0,
+        None => return None,
+    };
+
+    if text[line_start..].chars().all(|ch| ch == ' ' || ch == '\t') {
+        Some(line_start)
+    } else {
+        None
+    }
+}
+
+/// End of the first line break of the text, if only whitespace comes before it. 
+/// Text at the end of the file doesn't need the line break.
+fn line_break_end(text: &str, end_of_file: bool) -> Option<usize> {
+    let line_end = match text.find('\n') {
+        Some(newline) => newline + 1,
+        None if end_of_file => text.len(),
+        None => return None,
+    };
+
+    if text[..line_end].chars().all(|ch| ch.is_whitespace()) {
+        Some(line_end)
+    } else {
+        None
+    }
+}
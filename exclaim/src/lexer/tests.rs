@@ -1,27 +1,42 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
-    use crate::common::Location;
+    use crate::common::{
+        ExclaimError,
+        Location,
+        Span,
+    };
     use crate::tokens::*;
     use crate::lexer;
+    use crate::options::CompileOptions;
 
-    fn token_string_literal(string: &str, location: (usize, usize)) -> Token {
+    fn token_string_literal(string: &str, start: (usize, usize, usize), end: (usize, usize, usize)) -> Token<'_> {
         Token::StringLiteral(
-            string.to_string(),
-            Location::from(location)
+            string.into(),
+            span(start, end)
         )
     }
 
+    /// (line, column, offset) 
+    fn location((line, column, offset): (usize, usize, usize)) -> Location {
+        Location::with_offset(line, column, offset)
+    }
+
+    fn span(start: (usize, usize, usize), end: (usize, usize, usize)) -> Span {
+        Span::new(location(start), location(end))
+    }
+
     #[test]
     fn lexer_block_open() {
         let input = "test {{";
         let expected = vec![
-            token_string_literal("test ", (0, 0)),
-            Token::Operator(Op::BlockOpen, Location::new(0, 5))
+            token_string_literal("test ", (0, 0, 0), (0, 5, 5)),
+            Token::Operator(Op::BlockOpen, span((0, 5, 5), (0, 7, 7)))
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -31,14 +46,14 @@ mod tests {
     fn lexer_block_open_trick() {
         let input = "this is { a test {{{";
         let expected = vec![
-            token_string_literal("this is { a test ",  (0, 0)),
-            Token::Operator(Op::BlockOpen, Location::new(0, 17)), 
-            token_string_literal("{",  (0, 19)),
+            token_string_literal("this is { a test ",  (0, 0, 0), (0, 17, 17)),
+            Token::Operator(Op::BlockOpen, span((0, 17, 17), (0, 19, 19))), 
+            Token::Operator(Op::BlockOpenPrime, span((0, 19, 19), (0, 20, 20))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -48,13 +63,50 @@ mod tests {
     fn lexer_invalid_block_close() {
         let input = "This is a not a closed block }, and neither is this }}";
         let expected = vec![
-            token_string_literal("This is a not a closed block }, and neither is this ",  (0, 0)),
-            Token::Operator(Op::BlockClose, Location::new(0, 52)), 
+            token_string_literal("This is a not a closed block }, and neither is this }}",  (0, 0, 0), (0, 54, 54)),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_text_block_close_after_block() {
+        let input = "{{ write! a }} p { color: red; }} }}";
+        let expected = vec![
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Action(Action::Write, span((0, 3, 3), (0, 9, 9))),
+            Token::Label("a".into(), span((0, 10, 10), (0, 11, 11))),
+            Token::Operator(Op::BlockClose, span((0, 12, 12), (0, 14, 14))),
+            token_string_literal(" p { color: red; }} }}", (0, 14, 14), (0, 36, 36)),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_escaped_block_open() {
+        let input = "\\{{ write! a }} {{ write! b }}";
+        let expected = vec![
+            token_string_literal("{{ write! a }} ", (0, 0, 0), (0, 16, 16)),
+            Token::Operator(Op::BlockOpen, span((0, 16, 16), (0, 18, 18))),
+            Token::Action(Action::Write, span((0, 19, 19), (0, 25, 25))),
+            Token::Label("b".into(), span((0, 26, 26), (0, 27, 27))),
+            Token::Operator(Op::BlockClose, span((0, 28, 28), (0, 30, 30))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -64,13 +116,13 @@ mod tests {
     fn lexer_block_open_close() {
         let input = "{{}}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::Operator(Op::BlockClose, Location::new(0,2)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Operator(Op::BlockClose, span((0, 2, 2), (0, 4, 4))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -80,14 +132,14 @@ mod tests {
     fn lexer_block_uint() {
         let input = "{{ 1234 }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::NumberLiteral(Number::Uint(1234), Location::new(0,3)),
-            Token::Operator(Op::BlockClose, Location::new(0,8)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::NumberLiteral(Number::Uint(1234), span((0, 3, 3), (0, 7, 7))),
+            Token::Operator(Op::BlockClose, span((0, 8, 8), (0, 10, 10))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -97,14 +149,15 @@ mod tests {
     fn lexer_block_int() {
         let input = "{{ -1234 }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::NumberLiteral(Number::Int(-1234), Location::new(0,3)),
-            Token::Operator(Op::BlockClose, Location::new(0,9)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Operator(Op::Subtract, span((0, 3, 3), (0, 4, 4))),
+            Token::NumberLiteral(Number::Uint(1234), span((0, 4, 4), (0, 8, 8))),
+            Token::Operator(Op::BlockClose, span((0, 9, 9), (0, 11, 11))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -114,14 +167,14 @@ mod tests {
     fn lexer_block_float() {
         let input = "{{ 12.34 }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::NumberLiteral(Number::Float(12.34), Location::new(0,3)),
-            Token::Operator(Op::BlockClose, Location::new(0,9)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::NumberLiteral(Number::Float(12.34), span((0, 3, 3), (0, 8, 8))),
+            Token::Operator(Op::BlockClose, span((0, 9, 9), (0, 11, 11))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -131,54 +184,105 @@ mod tests {
     fn lexer_block_negative_float() {
         let input = "{{ -12.34 }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::NumberLiteral(Number::Float(-12.34), Location::new(0,3)),
-            Token::Operator(Op::BlockClose, Location::new(0,10)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Operator(Op::Subtract, span((0, 3, 3), (0, 4, 4))),
+            Token::NumberLiteral(Number::Float(12.34), span((0, 4, 4), (0, 9, 9))),
+            Token::Operator(Op::BlockClose, span((0, 10, 10), (0, 12, 12))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
     }
 
     #[test]
-    #[should_panic(expected = "Lexer<DIGIT>: The expected number contains invalid digit \'a\' with stack \"1234\". On line [0; 7]:\n\t{{ 1234a }}\n\t       ^ expected digit")]
+    #[should_panic(expected = "Lexer<NUMBER>: The number \"1234a\" contains invalid digit \'a\'. On line [0; 7]:\n\t{{ 1234a }}\n\t       ^ expected digit")]
     fn lexer_block_invalid_digit() {
         let input = "{{ 1234a }}";
         let _actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
     }
 
+    #[test]
+    fn lexer_block_invalid_digit_error() {
+        let input = "{{ 1234a }}";
+        let error = lexer::run(input).unwrap_err();
+
+        match error {
+            ExclaimError::Lex(location, _) => assert_eq!(location, Location::with_offset(0, 7, 7)),
+            _ => panic!("Expected a lexer error, but got: {}", error),
+        }
+    }
     
     #[test]
     fn lexer_block_label() {
         let input = "{{label_label}}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::Label(String::from("label_label"), Location::new(0,2)),
-            Token::Operator(Op::BlockClose, Location::new(0,13)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Label("label_label".into(), span((0, 2, 2), (0, 13, 13))),
+            Token::Operator(Op::BlockClose, span((0, 13, 13), (0, 15, 15))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_block_label_digits_underscore() {
+        let input = "{{ b1234 _id address_line1 ñandú2 }}";
+        let expected = vec![
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Label("b1234".into(), span((0, 3, 3), (0, 8, 8))),
+            Token::Label("_id".into(), span((0, 9, 9), (0, 12, 12))),
+            Token::Label("address_line1".into(), span((0, 13, 13), (0, 26, 26))),
+            Token::Label("ñandú2".into(), span((0, 27, 27), (0, 33, 35))),
+            Token::Operator(Op::BlockClose, span((0, 34, 36), (0, 36, 38))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_block_action_after_label_rules() {
+        let input = "{{ let! _item2 = item_1 }}";
+        let expected = vec![
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Action(Action::Let, span((0, 3, 3), (0, 7, 7))),
+            Token::Label("_item2".into(), span((0, 8, 8), (0, 14, 14))),
+            Token::Operator(Op::Assign, span((0, 15, 15), (0, 16, 16))),
+            Token::Label("item_1".into(), span((0, 17, 17), (0, 23, 23))),
+            Token::Operator(Op::BlockClose, span((0, 24, 24), (0, 26, 26))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
     }
 
     #[test]
-    #[should_panic(expected = "Lexer<LABEL>: The expected label contains digit \'1\' with stack \"b\". On line [0; 4]:\n\t{{ b1234 }}\n\t    ^ expected alphabetic character")]
-    fn lexer_block_invalid_label() {
-        let input = "{{ b1234 }}";
+    #[should_panic(expected = "Lexer<LABEL>: The expected action does not match any defined action - invalid action found: 'let2!'")]
+    fn lexer_block_invalid_action_with_digit() {
+        let input = "{{ let2! a = 1 }}";
         let _actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
     }
 
@@ -186,14 +290,14 @@ mod tests {
     fn lexer_block_string_literal() {
         let input = "{{ \"string \\\" literal\" }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::StringLiteral(String::from("string \" literal"), Location::new(0,3)),
-            Token::Operator(Op::BlockClose, Location::new(0,23)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::StringLiteral("string \" literal".into(), span((0, 3, 3), (0, 22, 22))),
+            Token::Operator(Op::BlockClose, span((0, 23, 23), (0, 25, 25))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -204,34 +308,501 @@ mod tests {
     fn lexer_block_action() {
         let input = "{{ render! }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::Action(Action::Render, Location::new(0,3)),
-            Token::Operator(Op::BlockClose, Location::new(0,11)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Action(Action::Render, span((0, 3, 3), (0, 10, 10))),
+            Token::Operator(Op::BlockClose, span((0, 11, 11), (0, 13, 13))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_block_action_include() {
+        let input = "{{ include! \"header.html\" }}";
+        let expected = vec![
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Action(Action::Include, span((0, 3, 3), (0, 11, 11))),
+            Token::StringLiteral("header.html".into(), span((0, 12, 12), (0, 25, 25))),
+            Token::Operator(Op::BlockClose, span((0, 26, 26), (0, 28, 28))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_block_action_inheritance() {
+        let input = "{{ extends! \"base\" }}{{ block! content }}{{ super! }}";
+        let expected = vec![
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Action(Action::Extends, span((0, 3, 3), (0, 11, 11))),
+            Token::StringLiteral("base".into(), span((0, 12, 12), (0, 18, 18))),
+            Token::Operator(Op::BlockClose, span((0, 19, 19), (0, 21, 21))),
+            Token::Operator(Op::BlockOpen, span((0, 21, 21), (0, 23, 23))),
+            Token::Action(Action::Block, span((0, 24, 24), (0, 30, 30))),
+            Token::Label("content".into(), span((0, 31, 31), (0, 38, 38))),
+            Token::Operator(Op::BlockClose, span((0, 39, 39), (0, 41, 41))),
+            Token::Operator(Op::BlockOpen, span((0, 41, 41), (0, 43, 43))),
+            Token::Action(Action::Super, span((0, 44, 44), (0, 50, 50))),
+            Token::Operator(Op::BlockClose, span((0, 51, 51), (0, 53, 53))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_trim_markers() {
+        let input = "a \n{{- write! b -}}\n c";
+        let expected = vec![
+            Token::StringLiteral("a".into(), span((0, 0, 0), (0, 1, 1))),
+            Token::Operator(Op::BlockOpen, span((1, 0, 3), (1, 2, 5))),
+            Token::Action(Action::Write, span((1, 4, 7), (1, 10, 13))),
+            Token::Label("b".into(), span((1, 11, 14), (1, 12, 15))),
+            Token::Operator(Op::BlockClose, span((1, 14, 17), (1, 16, 19))),
+            Token::StringLiteral("c".into(), span((2, 1, 21), (2, 2, 22))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_trim_marker_negative_number() {
+        let input = "{{ write! -1 }}";
+        let expected = vec![
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Action(Action::Write, span((0, 3, 3), (0, 9, 9))),
+            Token::Operator(Op::Subtract, span((0, 10, 10), (0, 11, 11))),
+            Token::NumberLiteral(Number::Uint(1), span((0, 11, 11), (0, 12, 12))),
+            Token::Operator(Op::BlockClose, span((0, 13, 13), (0, 15, 15))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_trim_marker_before_number() {
+        // A '-' next to the delimiters is always a trim marker, never a negation or a subtraction
+        let input = "a {{-5}} {{ write! 1 -2-}} b";
+        let expected = vec![
+            Token::StringLiteral("a".into(), span((0, 0, 0), (0, 1, 1))),
+            Token::Operator(Op::BlockOpen, span((0, 2, 2), (0, 4, 4))),
+            Token::NumberLiteral(Number::Uint(5), span((0, 5, 5), (0, 6, 6))),
+            Token::Operator(Op::BlockClose, span((0, 6, 6), (0, 8, 8))),
+            Token::StringLiteral(" ".into(), span((0, 8, 8), (0, 9, 9))),
+            Token::Operator(Op::BlockOpen, span((0, 9, 9), (0, 11, 11))),
+            Token::Action(Action::Write, span((0, 12, 12), (0, 18, 18))),
+            Token::NumberLiteral(Number::Uint(1), span((0, 19, 19), (0, 20, 20))),
+            Token::Operator(Op::Subtract, span((0, 21, 21), (0, 22, 22))),
+            Token::NumberLiteral(Number::Uint(2), span((0, 22, 22), (0, 23, 23))),
+            Token::Operator(Op::BlockClose, span((0, 24, 24), (0, 26, 26))),
+            Token::StringLiteral("b".into(), span((0, 27, 27), (0, 28, 28))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_comment() {
+        let input = "a{{# comment {{ write! b }} \n still a comment #}}\n{{ write! c }}";
+        let expected = vec![
+            Token::StringLiteral("a".into(), span((0, 0, 0), (0, 1, 1))),
+            Token::StringLiteral("\n".into(), span((1, 20, 49), (2, 0, 50))),
+            Token::Operator(Op::BlockOpen, span((2, 0, 50), (2, 2, 52))),
+            Token::Action(Action::Write, span((2, 3, 53), (2, 9, 59))),
+            Token::Label("c".into(), span((2, 10, 60), (2, 11, 61))),
+            Token::Operator(Op::BlockClose, span((2, 12, 62), (2, 14, 64))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_comment_unclosed_error() {
+        let input = "text {{# comment }}";
+
+        match lexer::run(input) {
+            Ok(tokens) => panic!("Expected an error, but got: {:?}", tokens),
+            Err(e) => {
+                assert_eq!(e.location(), Location::with_offset(0, 5, 5));
+                assert!(e.message().starts_with("Lexer<COMMENT>: The comment is never closed."));
+            },
+        }
+    }
+
+    #[test]
+    fn lexer_raw_block() {
+        let input = "a{{ raw! }}{{ write! b }}\n}}{{!}}{{ write! c }}";
+        let expected = vec![
+            Token::StringLiteral("a".into(), span((0, 0, 0), (0, 1, 1))),
+            Token::StringLiteral("{{ write! b }}\n}}".into(), span((0, 11, 11), (1, 2, 28))),
+            Token::Operator(Op::BlockOpen, span((1, 7, 33), (1, 9, 35))),
+            Token::Action(Action::Write, span((1, 10, 36), (1, 16, 42))),
+            Token::Label("c".into(), span((1, 17, 43), (1, 18, 44))),
+            Token::Operator(Op::BlockClose, span((1, 19, 45), (1, 21, 47))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_raw_block_close_with_whitespace_and_trim_markers() {
+        let input = "a {{- raw! -}}\n {{ write! b }} {{-\n!-}}\n c{{ raw! }}d{{ ! }}";
+        let expected = vec![
+            Token::StringLiteral("a".into(), span((0, 0, 0), (0, 1, 1))),
+            Token::StringLiteral("{{ write! b }}".into(), span((1, 1, 16), (1, 15, 30))),
+            Token::StringLiteral("c".into(), span((3, 1, 41), (3, 2, 42))),
+            Token::StringLiteral("d".into(), span((3, 12, 52), (3, 13, 53))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_raw_block_unclosed_error() {
+        let input = "{{ raw! }}{{ write! b }}";
+
+        match lexer::run(input) {
+            Ok(tokens) => panic!("Expected an error, but got: {:?}", tokens),
+            Err(e) => {
+                assert_eq!(e.location(), Location::with_offset(0, 8, 8));
+                assert!(e.message().starts_with("Lexer<RAW>: The raw! block is never closed."));
+            },
+        }
+    }
+
+    #[test]
+    fn lexer_raw_not_at_block_start_error() {
+        let input = "{{ write! raw! }}{{!}}";
+
+        match lexer::run(input) {
+            Ok(tokens) => panic!("Expected an error, but got: {:?}", tokens),
+            Err(e) => assert!(e.message().starts_with("Lexer<RAW>: raw! can only be used at the start of a block.")),
+        }
+    }
+
+    #[test]
+    fn lexer_custom_delimiters() {
+        let input = "{{ a }} <% write! b %>";
+        let options = crate::options::CompileOptions::new().delimiters("<%", "%>");
+        let expected = vec![
+            token_string_literal("{{ a }} ", (0, 0, 0), (0, 8, 8)),
+            Token::Operator(Op::BlockOpen, span((0, 8, 8), (0, 10, 10))),
+            Token::Action(Action::Write, span((0, 11, 11), (0, 17, 17))),
+            Token::Label("b".into(), span((0, 18, 18), (0, 19, 19))),
+            Token::Operator(Op::BlockClose, span((0, 20, 20), (0, 22, 22))),
+        ];
+
+        let actual = match lexer::run_with_options(input, &options) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_string_literal_escapes() {
+        let input = r#"{{ "a\nb\tc\rd\\e\"f\0g\u{48}\u{1F600}" }}"#;
+        let expected = vec![
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::StringLiteral("a\nb\tc\rd\\e\"f\0gH\u{1F600}".into(), span((0, 3, 3), (0, 39, 39))),
+            Token::Operator(Op::BlockClose, span((0, 40, 40), (0, 42, 42))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn lexer_string_literal_invalid_escape_error() {
+        let input = r#"{{ write! "abc\q" }}"#;
+
+        match lexer::run(input) {
+            Ok(tokens) => panic!("Expected an error, but got: {:?}", tokens),
+            Err(e) => {
+                assert_eq!(e.location(), Location::with_offset(0, 14, 14));
+                assert!(e.message().starts_with("Lexer<STRING>: Unknown escape sequence '\\q' in string literal."));
+            },
+        }
+    }
+
+    #[test]
+    fn lexer_string_literal_invalid_unicode_escape_error() {
+        for input in [r#"{{ "\u0041" }}"#, r#"{{ "\u{}" }}"#, r#"{{ "\u{1234567}" }}"#, r#"{{ "\u{D800}" }}"#] {
+            match lexer::run(input) {
+                Ok(tokens) => panic!("Expected an error for {}, but got: {:?}", input, tokens),
+                Err(e) => {
+                    assert_eq!(e.location(), Location::with_offset(0, 4, 4));
+                    assert!(e.message().starts_with("Lexer<STRING>: "));
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn lexer_string_literal_unterminated_error() {
+        let input = "{{ write! \"abc }}\n";
+
+        match lexer::run(input) {
+            Ok(tokens) => panic!("Expected an error, but got: {:?}", tokens),
+            Err(e) => {
+                assert_eq!(e.location(), Location::with_offset(0, 10, 10));
+                assert_eq!(e.message(), concat!(
+                    "Lexer<STRING>: The string literal is never closed with '\"'. On line [0; 10]:\n",
+                    "\t{{ write! \"abc }}\n",
+                    "\t          ^ the string literal starts here",
+                ));
+            },
+        }
+    }
+
+    #[test]
+    fn lexer_multibyte_text_and_escapes() {
+        let input = "héllo \\{{ wörld {{ write! \"ü\\tß\" }}日本";
+        let expected = vec![
+            token_string_literal("héllo {{ wörld ", (0, 0, 0), (0, 16, 18)),
+            Token::Operator(Op::BlockOpen, span((0, 16, 18), (0, 18, 20))),
+            Token::Action(Action::Write, span((0, 19, 21), (0, 25, 27))),
+            token_string_literal("ü\tß", (0, 26, 28), (0, 32, 36)),
+            Token::Operator(Op::BlockClose, span((0, 33, 37), (0, 35, 39))),
+            token_string_literal("日本", (0, 35, 39), (0, 37, 45)),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_span_byte_ranges() {
+        let input = "héllo \n {{- write! \"ü\\n\" | len -}}  日本";
+        let expected = vec!["héllo", "{{", "write!", "\"ü\\n\"", "|", "len", "}}", "日本"];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        let sources: Vec<&str> = actual.iter().map(|token| &input[token.span().range()]).collect();
+        assert_eq!(sources, expected);
+    }
+
+    #[test]
+    fn lexer_standalone_lines_span_byte_ranges() {
+        let input = "<ul>\n  {{ render! a : b }}\n  <li>\n  {{!}}\n</ul>";
+        let options = CompileOptions::new().standalone_lines(true);
+
+        let actual = match lexer::run_with_options(input, &options) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        let texts: Vec<(&str, &str)> = actual.iter()
+            .filter_map(|token| token.string_literal().map(|text| (text, &input[token.span().range()])))
+            .collect();
+        assert_eq!(texts, vec![("<ul>\n", "<ul>\n"), ("  <li>\n", "  <li>\n"), ("</ul>", "</ul>")]);
+    }
+
+    #[test]
+    fn lexer_standalone_lines_crlf_span_byte_ranges() {
+        let input = "<ul>\r\n  {{ render! a : b }}\r\n  <li>\r\n  {{!}}\r\n</ul>";
+        let options = CompileOptions::new().standalone_lines(true).normalize_line_endings(true);
+
+        let actual = match lexer::run_with_options(input, &options) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        // The dropped '\r' are still part of the spans in the input
+        let texts: Vec<(&str, &str)> = actual.iter()
+            .filter_map(|token| token.string_literal().map(|text| (text, &input[token.span().range()])))
+            .collect();
+        assert_eq!(texts, vec![("<ul>\n", "<ul>\r\n"), ("  <li>\n", "  <li>\r\n"), ("</ul>", "</ul>")]);
+    }
+
+    #[test]
+    fn lexer_tokens_borrow_from_input() {
+        use std::borrow::Cow;
+
+        let input = r#"text {{ write! name | concat("plain", "tab\t") }} \{{ escaped"#;
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        // Only text that isn't contiguous in the input is copied
+        let borrowed: Vec<(&str, bool)> = actual.iter()
+            .filter_map(|token| match token {
+                Token::StringLiteral(text, _) | Token::Label(text, _) => Some((text.as_ref(), matches!(text, Cow::Borrowed(_)))),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(borrowed, vec![
+            ("text ", true),
+            ("name", true),
+            ("concat", true),
+            ("plain", true),
+            ("tab\t", false),
+            (" {{ escaped", false),
+        ]);
+    }
+
+    #[test]
+    fn lexer_bool_and_null_literals() {
+        let input = "{{ true false null truthy }}";
+        let expected = vec![
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::BoolLiteral(true, span((0, 3, 3), (0, 7, 7))),
+            Token::BoolLiteral(false, span((0, 8, 8), (0, 13, 13))),
+            Token::NullLiteral(span((0, 14, 14), (0, 18, 18))),
+            Token::Label("truthy".into(), span((0, 19, 19), (0, 25, 25))),
+            Token::Operator(Op::BlockClose, span((0, 26, 26), (0, 28, 28))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_number_literal_forms() {
+        let input = "{{ 1e6 2.5E-3 0xFF -0x10 1_000_000 -1_0.0_1 }}";
+        let expected = vec![
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::NumberLiteral(Number::Float(1e6), span((0, 3, 3), (0, 6, 6))),
+            Token::NumberLiteral(Number::Float(2.5e-3), span((0, 7, 7), (0, 13, 13))),
+            Token::NumberLiteral(Number::Uint(255), span((0, 14, 14), (0, 18, 18))),
+            Token::Operator(Op::Subtract, span((0, 19, 19), (0, 20, 20))),
+            Token::NumberLiteral(Number::Uint(16), span((0, 20, 20), (0, 24, 24))),
+            Token::NumberLiteral(Number::Uint(1_000_000), span((0, 25, 25), (0, 34, 34))),
+            Token::Operator(Op::Subtract, span((0, 35, 35), (0, 36, 36))),
+            Token::NumberLiteral(Number::Float(10.01), span((0, 36, 36), (0, 43, 43))),
+            Token::Operator(Op::BlockClose, span((0, 44, 44), (0, 46, 46))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_number_literal_limits() {
+        // The parser folds the '-' into the literal, so isize::MIN is lexed as its magnitude
+        let input = format!("{{{{ {} {} }}}}", usize::MAX, isize::MIN);
+        let expected = vec![
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::NumberLiteral(Number::Uint(usize::MAX), span((0, 3, 3), (0, 23, 23))),
+            Token::Operator(Op::Subtract, span((0, 24, 24), (0, 25, 25))),
+            Token::NumberLiteral(Number::Uint(isize::MIN.unsigned_abs()), span((0, 25, 25), (0, 44, 44))),
+            Token::Operator(Op::BlockClose, span((0, 45, 45), (0, 47, 47))),
+        ];
+
+        let actual = match lexer::run(&input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_number_literal_errors() {
+        let cases = [
+            ("{{ 99999999999999999999999 }}", (0, 3, 3), "Lexer<NUMBER>: The number \"99999999999999999999999\" does not fit in an unsigned integer."),
+            ("{{ 1e999 }}", (0, 3, 3), "Lexer<NUMBER>: The number \"1e999\" does not fit in a float."),
+            ("{{ 1.2.3 }}", (0, 6, 6), "Lexer<NUMBER>: Malformed number \"1.2.3\", unexpected '.'."),
+            ("{{ 1. }}", (0, 4, 4), "Lexer<NUMBER>: Malformed number \"1.\", expected a digit after '.'."),
+            ("{{ 1e }}", (0, 4, 4), "Lexer<NUMBER>: Malformed number \"1e\", expected digits in the exponent."),
+            ("{{ 1__0 }}", (0, 4, 4), "Lexer<NUMBER>: Malformed number \"1__0\", '_' can only separate digits."),
+            ("{{ 10_ }}", (0, 5, 5), "Lexer<NUMBER>: Malformed number \"10_\", '_' can only separate digits."),
+            ("{{ 0x }}", (0, 5, 5), "Lexer<NUMBER>: Malformed number \"0x\", expected hexadecimal digits after '0x'."),
+            ("{{ 0xFG }}", (0, 6, 6), "Lexer<NUMBER>: The number \"0xFG\" contains invalid digit 'G'."),
+        ];
+
+        for (input, start, message) in cases {
+            match lexer::run(input) {
+                Ok(tokens) => panic!("Expected an error for {}, but got: {:?}", input, tokens),
+                Err(e) => {
+                    assert_eq!(e.location(), location(start), "{}", input);
+                    assert!(e.message().starts_with(message), "{}", e.message());
+                },
+            }
+        }
+    }
+
     #[test]
     fn lexer_block_action_or_inequality() {
         let input = "{{ render!render!=abc }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::Action(Action::Render, Location::new(0,3)),
-            Token::Label(String::from("render"), Location::new(0,10)),
-            Token::Operator(Op::Inequality, Location::new(0,16)),
-            Token::Label(String::from("abc"), Location::new(0,18)),
-            Token::Operator(Op::BlockClose, Location::new(0,22)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Action(Action::Render, span((0, 3, 3), (0, 10, 10))),
+            Token::Label("render".into(), span((0, 10, 10), (0, 16, 16))),
+            Token::Operator(Op::Inequality, span((0, 16, 16), (0, 18, 18))),
+            Token::Label("abc".into(), span((0, 18, 18), (0, 21, 21))),
+            Token::Operator(Op::BlockClose, span((0, 22, 22), (0, 24, 24))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -243,7 +814,7 @@ mod tests {
         let input = "{{ abc! }}";
         let _actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
     }
 
@@ -251,18 +822,18 @@ mod tests {
     fn lexer_block_and() {
         let input = "{{ 1 && test && 3 }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::NumberLiteral(Number::Uint(1), Location::new(0,3)),
-            Token::Operator(Op::And, Location::new(0,5)),
-            Token::Label(String::from("test"), Location::new(0,8)),
-            Token::Operator(Op::And, Location::new(0,13)),
-            Token::NumberLiteral(Number::Uint(3), Location::new(0,16)),
-            Token::Operator(Op::BlockClose, Location::new(0,18)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::NumberLiteral(Number::Uint(1), span((0, 3, 3), (0, 4, 4))),
+            Token::Operator(Op::And, span((0, 5, 5), (0, 7, 7))),
+            Token::Label("test".into(), span((0, 8, 8), (0, 12, 12))),
+            Token::Operator(Op::And, span((0, 13, 13), (0, 15, 15))),
+            Token::NumberLiteral(Number::Uint(3), span((0, 16, 16), (0, 17, 17))),
+            Token::Operator(Op::BlockClose, span((0, 18, 18), (0, 20, 20))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -272,16 +843,16 @@ mod tests {
     fn lexer_block_assign() {
         let input = "{{ pages = site }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::Label(String::from("pages"), Location::new(0,3)),
-            Token::Operator(Op::Assign, Location::new(0,9)),
-            Token::Label(String::from("site"), Location::new(0,11)),
-            Token::Operator(Op::BlockClose, Location::new(0,16)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Label("pages".into(), span((0, 3, 3), (0, 8, 8))),
+            Token::Operator(Op::Assign, span((0, 9, 9), (0, 10, 10))),
+            Token::Label("site".into(), span((0, 11, 11), (0, 15, 15))),
+            Token::Operator(Op::BlockClose, span((0, 16, 16), (0, 18, 18))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -291,18 +862,18 @@ mod tests {
     fn lexer_block_comma() {
         let input = "{{ test, \"test\", 2 }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::Label(String::from("test"), Location::new(0,3)),
-            Token::Operator(Op::Comma, Location::new(0,7)),
-            Token::StringLiteral(String::from("test"), Location::new(0,9)),
-            Token::Operator(Op::Comma, Location::new(0,15)),
-            Token::NumberLiteral(Number::Uint(2), Location::new(0,17)),
-            Token::Operator(Op::BlockClose, Location::new(0,19)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Label("test".into(), span((0, 3, 3), (0, 7, 7))),
+            Token::Operator(Op::Comma, span((0, 7, 7), (0, 8, 8))),
+            Token::StringLiteral("test".into(), span((0, 9, 9), (0, 15, 15))),
+            Token::Operator(Op::Comma, span((0, 15, 15), (0, 16, 16))),
+            Token::NumberLiteral(Number::Uint(2), span((0, 17, 17), (0, 18, 18))),
+            Token::Operator(Op::BlockClose, span((0, 19, 19), (0, 21, 21))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -312,18 +883,18 @@ mod tests {
     fn lexer_block_closure() {
         let input = "{{ [self.album] }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::Operator(Op::ClosureOpen, Location::new(0,3)),
-            Token::Label(String::from("self"), Location::new(0,4)),
-            Token::Operator(Op::Dot, Location::new(0,8)),
-            Token::Label(String::from("album"), Location::new(0,9)),
-            Token::Operator(Op::ClosureClose, Location::new(0,14)),
-            Token::Operator(Op::BlockClose, Location::new(0,16)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Operator(Op::ClosureOpen, span((0, 3, 3), (0, 4, 4))),
+            Token::Label("self".into(), span((0, 4, 4), (0, 8, 8))),
+            Token::Operator(Op::Dot, span((0, 8, 8), (0, 9, 9))),
+            Token::Label("album".into(), span((0, 9, 9), (0, 14, 14))),
+            Token::Operator(Op::ClosureClose, span((0, 14, 14), (0, 15, 15))),
+            Token::Operator(Op::BlockClose, span((0, 16, 16), (0, 18, 18))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -333,16 +904,16 @@ mod tests {
     fn lexer_block_dot() {
         let input = "{{ site.posts }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::Label(String::from("site"), Location::new(0,3)),
-            Token::Operator(Op::Dot, Location::new(0,7)),
-            Token::Label(String::from("posts"), Location::new(0,8)),
-            Token::Operator(Op::BlockClose, Location::new(0,14)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Label("site".into(), span((0, 3, 3), (0, 7, 7))),
+            Token::Operator(Op::Dot, span((0, 7, 7), (0, 8, 8))),
+            Token::Label("posts".into(), span((0, 8, 8), (0, 13, 13))),
+            Token::Operator(Op::BlockClose, span((0, 14, 14), (0, 16, 16))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -352,16 +923,16 @@ mod tests {
     fn lexer_block_each() {
         let input = "{{ item : items }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::Label(String::from("item"), Location::new(0,3)),
-            Token::Operator(Op::Each, Location::new(0,8)),
-            Token::Label(String::from("items"), Location::new(0,10)),
-            Token::Operator(Op::BlockClose, Location::new(0,16)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Label("item".into(), span((0, 3, 3), (0, 7, 7))),
+            Token::Operator(Op::Each, span((0, 8, 8), (0, 9, 9))),
+            Token::Label("items".into(), span((0, 10, 10), (0, 15, 15))),
+            Token::Operator(Op::BlockClose, span((0, 16, 16), (0, 18, 18))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -371,18 +942,18 @@ mod tests {
     fn lexer_block_equality() {
         let input = "{{ falsy = 1 == 2 }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::Label(String::from("falsy"), Location::new(0,3)),
-            Token::Operator(Op::Assign, Location::new(0,9)),
-            Token::NumberLiteral(Number::Uint(1), Location::new(0,11)),
-            Token::Operator(Op::Equality, Location::new(0,13)),
-            Token::NumberLiteral(Number::Uint(2), Location::new(0,16)),
-            Token::Operator(Op::BlockClose, Location::new(0,18)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Label("falsy".into(), span((0, 3, 3), (0, 8, 8))),
+            Token::Operator(Op::Assign, span((0, 9, 9), (0, 10, 10))),
+            Token::NumberLiteral(Number::Uint(1), span((0, 11, 11), (0, 12, 12))),
+            Token::Operator(Op::Equality, span((0, 13, 13), (0, 15, 15))),
+            Token::NumberLiteral(Number::Uint(2), span((0, 16, 16), (0, 17, 17))),
+            Token::Operator(Op::BlockClose, span((0, 18, 18), (0, 20, 20))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -392,18 +963,18 @@ mod tests {
     fn lexer_block_inequality() {
         let input = "{{ truthy = 1 != 2 }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::Label(String::from("truthy"), Location::new(0,3)),
-            Token::Operator(Op::Assign, Location::new(0,10)),
-            Token::NumberLiteral(Number::Uint(1), Location::new(0,12)),
-            Token::Operator(Op::Inequality, Location::new(0,14)),
-            Token::NumberLiteral(Number::Uint(2), Location::new(0,17)),
-            Token::Operator(Op::BlockClose, Location::new(0,19)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Label("truthy".into(), span((0, 3, 3), (0, 9, 9))),
+            Token::Operator(Op::Assign, span((0, 10, 10), (0, 11, 11))),
+            Token::NumberLiteral(Number::Uint(1), span((0, 12, 12), (0, 13, 13))),
+            Token::Operator(Op::Inequality, span((0, 14, 14), (0, 16, 16))),
+            Token::NumberLiteral(Number::Uint(2), span((0, 17, 17), (0, 18, 18))),
+            Token::Operator(Op::BlockClose, span((0, 19, 19), (0, 21, 21))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -413,18 +984,18 @@ mod tests {
     fn lexer_block_or() {
         let input = "{{ 1 || test || 3 }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::NumberLiteral(Number::Uint(1), Location::new(0,3)),
-            Token::Operator(Op::Or, Location::new(0,5)),
-            Token::Label(String::from("test"),Location::new(0,8)),
-            Token::Operator(Op::Or, Location::new(0,13)),
-            Token::NumberLiteral(Number::Uint(3), Location::new(0,16)),
-            Token::Operator(Op::BlockClose, Location::new(0,18)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::NumberLiteral(Number::Uint(1), span((0, 3, 3), (0, 4, 4))),
+            Token::Operator(Op::Or, span((0, 5, 5), (0, 7, 7))),
+            Token::Label("test".into(),span((0, 8, 8), (0, 12, 12))),
+            Token::Operator(Op::Or, span((0, 13, 13), (0, 15, 15))),
+            Token::NumberLiteral(Number::Uint(3), span((0, 16, 16), (0, 17, 17))),
+            Token::Operator(Op::BlockClose, span((0, 18, 18), (0, 20, 20))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -436,26 +1007,99 @@ mod tests {
         let input = "test\n{{ `` }}\ntest";
         let _actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
+        };
+    }
+
+    #[test]
+    #[should_panic(expected = "Lexer<BLOCK>: Encountered unknown character \'`\'. On line [1; 14]:\n\t    日本 {{ write! ` }}\n\t                   ^ unknown character")]
+    fn lexer_block_unknown_character_display_columns() {
+        // The tab is expanded to the next tab stop and the wide characters take two columns each 
+        let input = "test\r\n\t日本 {{ write! ` }}";
+        let _actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+    }
+
+    #[test]
+    fn lexer_byte_order_mark() {
+        let input = "\u{FEFF}Hello {{ write! a }}";
+        let expected = vec![
+            token_string_literal("Hello ", (0, 0, 3), (0, 6, 9)),
+            Token::Operator(Op::BlockOpen, span((0, 6, 9), (0, 8, 11))),
+            Token::Action(Action::Write, span((0, 9, 12), (0, 15, 18))),
+            Token::Label("a".into(), span((0, 16, 19), (0, 17, 20))),
+            Token::Operator(Op::BlockClose, span((0, 18, 21), (0, 20, 23))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
         };
+
+        assert_eq!(actual, expected);
+        assert_eq!(&input[actual[0].span().range()], "Hello ");
+    }
+
+    #[test]
+    fn lexer_line_endings_preserved() {
+        let input = "a\r\n{{ write! \"b\r\nc\" }}\r\nd";
+        let expected = vec![
+            token_string_literal("a\r\n", (0, 0, 0), (1, 0, 3)),
+            Token::Operator(Op::BlockOpen, span((1, 0, 3), (1, 2, 5))),
+            Token::Action(Action::Write, span((1, 3, 6), (1, 9, 12))),
+            Token::StringLiteral("b\r\nc".into(), span((1, 10, 13), (2, 2, 19))),
+            Token::Operator(Op::BlockClose, span((2, 3, 20), (2, 5, 22))),
+            token_string_literal("\r\nd", (2, 5, 22), (3, 1, 25)),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_line_endings_normalized() {
+        let input = "a\r\n{{ write! \"b\r\nc\" }}\r\nd\re";
+        let options = CompileOptions::new().normalize_line_endings(true);
+        let expected = vec![
+            token_string_literal("a\n", (0, 0, 0), (1, 0, 3)),
+            Token::Operator(Op::BlockOpen, span((1, 0, 3), (1, 2, 5))),
+            Token::Action(Action::Write, span((1, 3, 6), (1, 9, 12))),
+            Token::StringLiteral("b\nc".into(), span((1, 10, 13), (2, 2, 19))),
+            Token::Operator(Op::BlockClose, span((2, 3, 20), (2, 5, 22))),
+            token_string_literal("\nd\re", (2, 5, 22), (3, 3, 27)),
+        ];
+
+        let actual = match lexer::run_with_options(input, &options) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+        assert_eq!(&input[actual[0].span().range()], "a\r\n");
     }
 
     #[test]
     fn lexer_block_pipe() {
         let input = "{{ posts | reverse | take }}";
         let expected = vec![
-            Token::Operator(Op::BlockOpen, Location::new(0,0)),
-            Token::Label(String::from("posts"), Location::new(0,3)),
-            Token::Operator(Op::Pipe, Location::new(0,9)),
-            Token::Label(String::from("reverse"),Location::new(0,11)),
-            Token::Operator(Op::Pipe, Location::new(0,19)),
-            Token::Label(String::from("take"), Location::new(0,21)),
-            Token::Operator(Op::BlockClose, Location::new(0,26)),
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Label("posts".into(), span((0, 3, 3), (0, 8, 8))),
+            Token::Operator(Op::Pipe, span((0, 9, 9), (0, 10, 10))),
+            Token::Label("reverse".into(),span((0, 11, 11), (0, 18, 18))),
+            Token::Operator(Op::Pipe, span((0, 19, 19), (0, 20, 20))),
+            Token::Label("take".into(), span((0, 21, 21), (0, 25, 25))),
+            Token::Operator(Op::BlockClose, span((0, 26, 26), (0, 28, 28))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
@@ -466,39 +1110,158 @@ mod tests {
     fn lexer_simple() {
         let input = "<h1>Tests</h1>\n{{ render! tests : site.tests | take(1,5) }}\n<li>{{ tests.name }}</li>\n{{!}}";
         let expected = vec![
-            token_string_literal("<h1>Tests</h1>\n",  (0, 0)),
-            Token::Operator(Op::BlockOpen, Location::new(1,0)),
-            Token::Action(Action::Render, Location::new(1,3)),
-            Token::Label(String::from("tests"), Location::new(1,11)),
-            Token::Operator(Op::Each, Location::new(1,17)),
-            Token::Label(String::from("site"), Location::new(1,19)),
-            Token::Operator(Op::Dot, Location::new(1,23)),
-            Token::Label(String::from("tests"), Location::new(1,24)),
-            Token::Operator(Op::Pipe, Location::new(1,30)),
-            Token::Label(String::from("take"), Location::new(1,32)),
-            Token::Operator(Op::ParenOpen, Location::new(1,36)),
-            Token::NumberLiteral(Number::Uint(1), Location::new(1,37)),
-            Token::Operator(Op::Comma, Location::new(1,38)),
-            Token::NumberLiteral(Number::Uint(5), Location::new(1,39)),
-            Token::Operator(Op::ParenClose, Location::new(1,40)),
-            Token::Operator(Op::BlockClose, Location::new(1,42)),
-            token_string_literal("\n<li>", (1, 44)),
-            Token::Operator(Op::BlockOpen, Location::new(2,4)),
-            Token::Label(String::from("tests"), Location::new(2,7)),
-            Token::Operator(Op::Dot, Location::new(2,12)),
-            Token::Label(String::from("name"), Location::new(2,13)),
-            Token::Operator(Op::BlockClose, Location::new(2,18)),
-            token_string_literal("</li>\n", (2, 20)),
-            Token::Operator(Op::BlockOpen, Location::new(3,0)),
-            Token::Action(Action::End, Location::new(3,2)),
-            Token::Operator(Op::BlockClose, Location::new(3,3)),
+            token_string_literal("<h1>Tests</h1>\n",  (0, 0, 0), (1, 0, 15)),
+            Token::Operator(Op::BlockOpen, span((1, 0, 15), (1, 2, 17))),
+            Token::Action(Action::Render, span((1, 3, 18), (1, 10, 25))),
+            Token::Label("tests".into(), span((1, 11, 26), (1, 16, 31))),
+            Token::Operator(Op::Each, span((1, 17, 32), (1, 18, 33))),
+            Token::Label("site".into(), span((1, 19, 34), (1, 23, 38))),
+            Token::Operator(Op::Dot, span((1, 23, 38), (1, 24, 39))),
+            Token::Label("tests".into(), span((1, 24, 39), (1, 29, 44))),
+            Token::Operator(Op::Pipe, span((1, 30, 45), (1, 31, 46))),
+            Token::Label("take".into(), span((1, 32, 47), (1, 36, 51))),
+            Token::Operator(Op::ParenOpen, span((1, 36, 51), (1, 37, 52))),
+            Token::NumberLiteral(Number::Uint(1), span((1, 37, 52), (1, 38, 53))),
+            Token::Operator(Op::Comma, span((1, 38, 53), (1, 39, 54))),
+            Token::NumberLiteral(Number::Uint(5), span((1, 39, 54), (1, 40, 55))),
+            Token::Operator(Op::ParenClose, span((1, 40, 55), (1, 41, 56))),
+            Token::Operator(Op::BlockClose, span((1, 42, 57), (1, 44, 59))),
+            token_string_literal("\n<li>", (1, 44, 59), (2, 4, 64)),
+            Token::Operator(Op::BlockOpen, span((2, 4, 64), (2, 6, 66))),
+            Token::Label("tests".into(), span((2, 7, 67), (2, 12, 72))),
+            Token::Operator(Op::Dot, span((2, 12, 72), (2, 13, 73))),
+            Token::Label("name".into(), span((2, 13, 73), (2, 17, 77))),
+            Token::Operator(Op::BlockClose, span((2, 18, 78), (2, 20, 80))),
+            token_string_literal("</li>\n", (2, 20, 80), (3, 0, 86)),
+            Token::Operator(Op::BlockOpen, span((3, 0, 86), (3, 2, 88))),
+            Token::Action(Action::End, span((3, 2, 88), (3, 3, 89))),
+            Token::Operator(Op::BlockClose, span((3, 3, 89), (3, 5, 91))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_conditional_actions() {
+        let input = "{{ if! a }}{{ elif! b }}{{ else! }}{{!}}";
+        let expected = vec![
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Action(Action::If, span((0, 3, 3), (0, 6, 6))),
+            Token::Label("a".into(), span((0, 7, 7), (0, 8, 8))),
+            Token::Operator(Op::BlockClose, span((0, 9, 9), (0, 11, 11))),
+            Token::Operator(Op::BlockOpen, span((0, 11, 11), (0, 13, 13))),
+            Token::Action(Action::Elif, span((0, 14, 14), (0, 19, 19))),
+            Token::Label("b".into(), span((0, 20, 20), (0, 21, 21))),
+            Token::Operator(Op::BlockClose, span((0, 22, 22), (0, 24, 24))),
+            Token::Operator(Op::BlockOpen, span((0, 24, 24), (0, 26, 26))),
+            Token::Action(Action::Else, span((0, 27, 27), (0, 32, 32))),
+            Token::Operator(Op::BlockClose, span((0, 33, 33), (0, 35, 35))),
+            Token::Operator(Op::BlockOpen, span((0, 35, 35), (0, 37, 37))),
+            Token::Action(Action::End, span((0, 37, 37), (0, 38, 38))),
+            Token::Operator(Op::BlockClose, span((0, 38, 38), (0, 40, 40))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_comparison_and_not_operators() {
+        // A '!' right after the open delimiter is the end action 
+        let input = "{{ write! !a != b<c <= d > e>=f }}{{ !}}";
+        let expected = vec![
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Action(Action::Write, span((0, 3, 3), (0, 9, 9))),
+            Token::Operator(Op::Not, span((0, 10, 10), (0, 11, 11))),
+            Token::Label("a".into(), span((0, 11, 11), (0, 12, 12))),
+            Token::Operator(Op::Inequality, span((0, 13, 13), (0, 15, 15))),
+            Token::Label("b".into(), span((0, 16, 16), (0, 17, 17))),
+            Token::Operator(Op::Less, span((0, 17, 17), (0, 18, 18))),
+            Token::Label("c".into(), span((0, 18, 18), (0, 19, 19))),
+            Token::Operator(Op::LessEqual, span((0, 20, 20), (0, 22, 22))),
+            Token::Label("d".into(), span((0, 23, 23), (0, 24, 24))),
+            Token::Operator(Op::Greater, span((0, 25, 25), (0, 26, 26))),
+            Token::Label("e".into(), span((0, 27, 27), (0, 28, 28))),
+            Token::Operator(Op::GreaterEqual, span((0, 28, 28), (0, 30, 30))),
+            Token::Label("f".into(), span((0, 30, 30), (0, 31, 31))),
+            Token::Operator(Op::BlockClose, span((0, 32, 32), (0, 34, 34))),
+            Token::Operator(Op::BlockOpen, span((0, 34, 34), (0, 36, 36))),
+            Token::Action(Action::End, span((0, 37, 37), (0, 38, 38))),
+            Token::Operator(Op::BlockClose, span((0, 38, 38), (0, 40, 40))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_arithmetic_operators() {
+        // '-' is always an operator, the parser decides if it is a subtraction or a negation 
+        let input = "{{ write! (a-1) * -2 / 3 % b + \"c\" -}}";
+        let expected = vec![
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Action(Action::Write, span((0, 3, 3), (0, 9, 9))),
+            Token::Operator(Op::ParenOpen, span((0, 10, 10), (0, 11, 11))),
+            Token::Label("a".into(), span((0, 11, 11), (0, 12, 12))),
+            Token::Operator(Op::Subtract, span((0, 12, 12), (0, 13, 13))),
+            Token::NumberLiteral(Number::Uint(1), span((0, 13, 13), (0, 14, 14))),
+            Token::Operator(Op::ParenClose, span((0, 14, 14), (0, 15, 15))),
+            Token::Operator(Op::Multiply, span((0, 16, 16), (0, 17, 17))),
+            Token::Operator(Op::Subtract, span((0, 18, 18), (0, 19, 19))),
+            Token::NumberLiteral(Number::Uint(2), span((0, 19, 19), (0, 20, 20))),
+            Token::Operator(Op::Divide, span((0, 21, 21), (0, 22, 22))),
+            Token::NumberLiteral(Number::Uint(3), span((0, 23, 23), (0, 24, 24))),
+            Token::Operator(Op::Remainder, span((0, 25, 25), (0, 26, 26))),
+            Token::Label("b".into(), span((0, 27, 27), (0, 28, 28))),
+            Token::Operator(Op::Add, span((0, 29, 29), (0, 30, 30))),
+            token_string_literal("c", (0, 31, 31), (0, 34, 34)),
+            Token::Operator(Op::BlockClose, span((0, 36, 36), (0, 38, 38))),
+        ];
+
+        let actual = match lexer::run(input) {
+            Ok(tokens) => tokens,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lexer_collection_literals() {
+        let input = "{{ let! x = { a: [1] } }}";
+        let expected = vec![
+            Token::Operator(Op::BlockOpen, span((0, 0, 0), (0, 2, 2))),
+            Token::Action(Action::Let, span((0, 3, 3), (0, 7, 7))),
+            Token::Label("x".into(), span((0, 8, 8), (0, 9, 9))),
+            Token::Operator(Op::Assign, span((0, 10, 10), (0, 11, 11))),
+            Token::Operator(Op::BlockOpenPrime, span((0, 12, 12), (0, 13, 13))),
+            Token::Label("a".into(), span((0, 14, 14), (0, 15, 15))),
+            Token::Operator(Op::Each, span((0, 15, 15), (0, 16, 16))),
+            Token::Operator(Op::ClosureOpen, span((0, 17, 17), (0, 18, 18))),
+            Token::NumberLiteral(Number::Uint(1), span((0, 18, 18), (0, 19, 19))),
+            Token::Operator(Op::ClosureClose, span((0, 19, 19), (0, 20, 20))),
+            Token::Operator(Op::BlockClosePrime, span((0, 21, 21), (0, 22, 22))),
+            Token::Operator(Op::BlockClose, span((0, 23, 23), (0, 25, 25))),
         ];
 
         let actual = match lexer::run(input) {
             Ok(tokens) => tokens,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
 
         assert_eq!(actual, expected);
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,5 @@
-
-
 pub mod common;
+pub use common::ExclaimError;
 
 mod ast;
 use ast::prelude::*;
@@ -15,37 +14,71 @@ mod parser;
 mod semantics;
 mod runtime;
 
+mod options;
+pub use options::CompileOptions;
+
+mod template;
+pub use template::Template;
+
+mod environment;
+pub use environment::Environment;
+
 pub fn run(input: &str, data: Option<DataContext>) -> String {
-    let tokens = run_lexer(input);
-    let ast = run_parser(tokens);
-    let ast = run_semantics(ast);
-    run_runtime(ast, data)
+    match try_run(input, data) {
+        Ok(output) => output,
+        Err(e) => panic!("Exclaim failed with the error:\n{}", e),
+    }
 }
 
-pub fn run_lexer<S: AsRef<str>>(input: S) -> Vec<tokens::Token> {
-    match lexer::run(input) {
+pub fn run_lexer(input: &str) -> Vec<tokens::Token<'_>> {
+    match try_run_lexer(input) {
         Ok(tokens) => tokens,
-        Err(e) => panic!("Lexer failed with the error:\n{:?}", e),
+        Err(e) => panic!("Lexer failed with the error:\n{}", e),
     }
 }
 
-pub fn run_parser(input: Vec<tokens::Token>) -> Ast {
-    match parser::run(input) {
+pub fn run_parser(input: Vec<tokens::Token<'_>>) -> Ast<'_> {
+    match try_run_parser(input) {
         Ok(ast) => ast,
-        Err(e) => panic!("Parser failed with the error:\n{:?}", e),
+        Err(e) => panic!("Parser failed with the error:\n{}", e),
     }
 }
 
-pub fn run_semantics(input: Ast) -> Ast {
-    match semantics::run(input) {
+pub fn run_semantics(input: Ast<'_>) -> Ast<'_> {
+    match try_run_semantics(input) {
         Ok(ast) => ast,
-        Err(e) => panic!("Semantic Analysis failed with the error:\n{:?}", e),
+        Err(e) => panic!("Semantic Analysis failed with the error:\n{}", e),
     }
 }
 
 pub fn run_runtime(input: Ast, data: Option<DataContext>) -> String {
-    match runtime::run(input, data) {
+    match try_run_runtime(input, data) {
         Ok(output) => output,
-        Err(e) => panic!("Runtime failed with the error:\n{:?}", e),
+        Err(e) => panic!("Runtime failed with the error:\n{}", e),
     }
-}
\ No newline at end of file
+}
+
+pub fn try_run(input: &str, data: Option<DataContext>) -> Result<String, ExclaimError> {
+    let tokens = try_run_lexer(input)?;
+    let ast = parser::run_with_source(tokens, input)?;
+    let ast = try_run_semantics(ast)?;
+    try_run_runtime(ast, data)
+}
+
+pub fn try_run_lexer(input: &str) -> Result<Vec<tokens::Token<'_>>, ExclaimError> {
+    lexer::run(input)
+}
+
+/// The tokens don't carry the source they were lexed from, so unlike try_run and Template::compile the errors
+/// don't include the line of the source with the offending token underlined.
+pub fn try_run_parser(input: Vec<tokens::Token<'_>>) -> Result<Ast<'_>, ExclaimError> {
+    parser::run(input)
+}
+
+pub fn try_run_semantics(input: Ast<'_>) -> Result<Ast<'_>, ExclaimError> {
+    semantics::run(input)
+}
+
+pub fn try_run_runtime(input: Ast, data: Option<DataContext>) -> Result<String, ExclaimError> {
+    runtime::run(&input, data.as_ref())
+}
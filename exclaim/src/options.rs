@@ -0,0 +1,66 @@
+/// Options changing how a template is compiled.
+/// 
+/// The default options compile templates exactly as they are written.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CompileOptions {
+    standalone_lines: bool,
+    normalize_line_endings: bool,
+    open_delimiter: String,
+    close_delimiter: String,
+}
+
+impl CompileOptions {
+    pub fn new() -> CompileOptions {
+        CompileOptions::default()
+    }
+
+    /// Replaces the delimiters of code blocks, '{{' and '}}' by default. 
+    /// Comments, trim markers and raw! regions use the new delimiters too, e.g. '<%#' ... '#%>'.
+    /// Delimiters can't be empty or contain whitespace, this is checked when the template is compiled.
+    pub fn delimiters(mut self, open: &str, close: &str) -> CompileOptions {
+        self.open_delimiter = open.to_string();
+        self.close_delimiter = close.to_string();
+        self
+    }
+
+    pub fn open_delimiter(&self) -> &str {
+        &self.open_delimiter
+    }
+
+    pub fn close_delimiter(&self) -> &str {
+        &self.close_delimiter
+    }
+
+    /// When enabled, a line holding nothing but a let!, render!, if!, elif!, else!, block!, extends! or {{!}} block, a comment, 
+    /// or the delimiters of a raw! region (and indentation) is removed from the output, including its newline. 
+    /// Lines with a write!, include! or super! block are kept, since the block renders output.
+    pub fn standalone_lines(mut self, enabled: bool) -> CompileOptions {
+        self.standalone_lines = enabled;
+        self
+    }
+
+    pub fn strips_standalone_lines(&self) -> bool {
+        self.standalone_lines
+    }
+
+    /// When enabled, every "\r\n" line ending of the template is written as "\n". By default, line endings are kept as they are.
+    pub fn normalize_line_endings(mut self, enabled: bool) -> CompileOptions {
+        self.normalize_line_endings = enabled;
+        self
+    }
+
+    pub fn normalizes_line_endings(&self) -> bool {
+        self.normalize_line_endings
+    }
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        CompileOptions {
+            standalone_lines: false,
+            normalize_line_endings: false,
+            open_delimiter: String::from("{{"),
+            close_delimiter: String::from("}}"),
+        }
+    }
+}
@@ -1,8 +1,68 @@
 // This code's structure heavily inspired by Rust's std::io::error.rs
 use std::fmt;
 
+use crate::common::Location;
+use crate::tokens::Token;
+
 pub struct ParserError {
     error: Error,
+    location: Option<Location>,
+}
+
+impl ParserError {
+    /// The parser expected something else than the token it found 
+    pub fn unexpected(expected: &str, found: &Token) -> ParserError {
+        ParserError {
+            error: Error::Unexpected(expected.to_string(), Some(Box::new(found.clone().into_owned()))),
+            location: Some(found.location()),
+        }
+    }
+
+    /// The token stream ended while the parser still expected a token. 
+    /// The location is the location of the last token of the stream. 
+    pub fn end_of_token_stream(expected: &str, location: Location) -> ParserError {
+        ParserError {
+            error: Error::Unexpected(expected.to_string(), None),
+            location: Some(location),
+        }
+    }
+
+    /// The key of an object member was already declared by an earlier member
+    pub fn duplicate_key(name: &str, key: &Token) -> ParserError {
+        ParserError {
+            error: Error::DuplicateKey(name.to_string()),
+            location: Some(key.location()),
+        }
+    }
+
+    /// The expression nests deeper than the parser allows, so it stops instead of overflowing the stack
+    pub fn nested_too_deep(max_depth: usize, location: Location) -> ParserError {
+        ParserError {
+            error: Error::NestedTooDeep(max_depth),
+            location: Some(location),
+        }
+    }
+
+    /// Binds the error to the location if it isn't bound to a token
+    pub fn or_location(mut self, location: Location) -> ParserError {
+        self.location.get_or_insert(location);
+        self
+    }
+
+    /// Location of the offending token, if the error is bound to a token 
+    pub fn location(&self) -> Option<Location> {
+        self.location
+    }
+
+    /// Short message displayed under the offending token in a source snippet
+    pub fn underline(&self) -> String {
+        match &self.error {
+            Error::Unexpected(expected, _) => format!("expected {}", expected),
+            Error::DuplicateKey(_) => "duplicate key".to_string(),
+            Error::NestedTooDeep(_) => "nested too deeply".to_string(),
+            _ => self.to_string(),
+        }
+    }
 }
 
 impl fmt::Debug for ParserError {
@@ -11,10 +71,14 @@ impl fmt::Debug for ParserError {
     }
 }
 
-impl From<ErrorKind> for ParserError {
-    fn from(kind: ErrorKind) -> ParserError {
-        ParserError {
-            error: Error::Simple(kind),
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            Error::Custom(msg) => f.write_str(msg),
+            Error::Unexpected(expected, Some(found)) => write!(f, "Expected {}, but found '{}'.", expected, found),
+            Error::Unexpected(expected, None) => write!(f, "Expected {}, but reached the end of the template.", expected),
+            Error::DuplicateKey(name) => write!(f, "The key '{}' is declared more than once in the object.", name),
+            Error::NestedTooDeep(max_depth) => write!(f, "The expression is nested too deeply, at most {} levels of nesting are allowed.", max_depth),
         }
     }
 }
@@ -23,6 +87,7 @@ impl From<&str> for ParserError {
     fn from(msg: &str) -> ParserError {
         ParserError {
             error: Error::Custom(msg.to_string()),
+            location: None,
         }
     }
 }
@@ -31,42 +96,30 @@ impl From<String> for ParserError {
     fn from(msg: String) -> ParserError {
         ParserError {
             error: Error::Custom(msg),
+            location: None,
         }
     }
 }
 
 enum Error {
-    Simple(ErrorKind),
     Custom(String),
+    /// Unexpected(expected: String, found: Option<Box<Token>>) 
+    /// 
+    /// found is None when the token stream ended unexpectedly. The token is boxed to keep the error small.
+    Unexpected(String, Option<Box<Token<'static>>>),
+    /// DuplicateKey(name: String)
+    DuplicateKey(String),
+    /// NestedTooDeep(max_depth: usize)
+    NestedTooDeep(usize),
 }
 
 impl fmt::Debug for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Error::Simple(kind) => f.debug_tuple("Kind").field(&kind).finish(),
             Error::Custom(msg) => f.debug_tuple("Custom").field(&msg).finish(),
+            Error::Unexpected(expected, found) => f.debug_tuple("Unexpected").field(&expected).field(&found).finish(),
+            Error::DuplicateKey(name) => f.debug_tuple("DuplicateKey").field(&name).finish(),
+            Error::NestedTooDeep(max_depth) => f.debug_tuple("NestedTooDeep").field(&max_depth).finish(),
         }
     }
 }
-
-pub enum ErrorKind {
-    // Reached a section of the parser that hasn't been implemented
-    // Unimplemented,
-    // End of Token Stream, but we expected another token 
-    UnexpectedEndOfTokenStream,
-}
-
-impl ErrorKind {
-    fn as_str(&self) -> &'static str {
-        match *self {
-            // ErrorKind::Unimplemented => "reached unimplemented section of parser",
-            ErrorKind::UnexpectedEndOfTokenStream => "unexpected end of token stream",
-        }
-    }
-}
-
-impl fmt::Debug for ErrorKind {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Debug::fmt(&self.as_str(), f)
-    }
-}
\ No newline at end of file
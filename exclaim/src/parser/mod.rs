@@ -1,7 +1,13 @@
 use std::result;
 use std::collections::LinkedList;
+use std::convert::TryFrom;
 
 use crate::ast::prelude::*;
+use crate::common::{
+    ExclaimError,
+    Location,
+    Span,
+};
 use crate::tokens::*;
 
 pub mod error;
@@ -9,59 +15,165 @@ use error::*;
 
 type Result<T> = result::Result<T, ParserError>;
 
-pub struct Parser(LinkedList<Token>);
+/// Expressions are parsed recursively, so their nesting is limited to keep deeply nested input from overflowing the stack
+const MAX_DEPTH: usize = 256;
+
+/// Parser(tokens: LinkedList<Token>, last: Span, depth: usize)
+/// 
+/// last is the span of the most recently consumed token 
+/// depth is the number of nested expressions being parsed 
+pub struct Parser<'a>(LinkedList<Token<'a>>, Span, usize);
 
 // Methods
-impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Parser {
-        Parser(tokens.into_iter().collect())
+impl<'a> Parser<'a> {
+    pub fn new(tokens: Vec<Token<'a>>) -> Parser<'a> {
+        Parser(tokens.into_iter().collect(), Span::default(), 0)
     }
 
-    fn peek(&self) -> Option<&Token> {
+    fn peek(&self) -> Option<&Token<'a>> {
         self.0.front()
     }
 
     /// Returns the token removed from the head of the list 
     /// If you see: let _ = parser.consume(), that means we needed to consume the Token, but the token isnt needed in the AST.
-    fn consume(&mut self) -> Token {
-        self.0.pop_front().unwrap()
+    fn consume(&mut self) -> Token<'a> {
+        let token = self.0.pop_front().unwrap();
+        self.1 = token.span();
+        token
+    }
+
+    /// Location of the next token, or the last consumed token if the stream is empty 
+    fn location(&self) -> Location {
+        match self.peek() {
+            Some(token) => token.location(),
+            None => self.1.start(),
+        }
+    }
+
+    /// Pushes the node onto the ast, spanning from start to the end of the most recently consumed token 
+    fn push_spanned<T>(&self, ast: &mut Ast<'a>, start: Location, node: T) -> AstIndex where Ast<'a>: Pushable<T> {
+        let index = ast.push(node);
+        ast.set_span(index, Span::new(start, self.1.end()));
+        index
+    }
+
+    /// Parses one level deeper, or fails at the next token when the expression is nested deeper than MAX_DEPTH 
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Parser<'a>) -> Result<T>) -> Result<T> {
+        if self.2 >= MAX_DEPTH {
+            return Err(ParserError::nested_too_deep(MAX_DEPTH, self.location()));
+        }
+
+        self.2 += 1;
+        let result = parse(self);
+        self.2 -= 1;
+        result
     }
 
     fn end_of_token_stream(&self) -> bool {
         self.0.is_empty()
     }
+
+    fn remaining(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Skips tokens until the end of the current code block '}}' has been consumed, or the next block '{{' is reached.
+    /// If the block failed before consuming any token, the offending token is skipped so the parser always makes progress.
+    fn synchronize(&mut self, remaining: usize) {
+        if self.remaining() == remaining && !self.end_of_token_stream() {
+            if let Token::Operator(Op::BlockClose, _) = self.consume() {
+                return;
+            }
+        }
+
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Operator(Op::BlockOpen, _) => break,
+                Token::Operator(Op::BlockClose, _) => {
+                    let _block_close = self.consume();
+                    break;
+                },
+                _ => {
+                    let _ = self.consume();
+                },
+            }
+        }
+    }
 }
 
+/// Peeks the next token, or returns an error describing what was expected when the token stream is empty 
 macro_rules! unwrap_token {
-    ($token:expr) => {
-        if let Some(token) = $token {
+    ($parser:expr, $expected:expr) => {
+        if let Some(token) = $parser.peek() {
             token
         } else {
-            return Err(ParserError::from(ErrorKind::UnexpectedEndOfTokenStream))
+            return Err(ParserError::end_of_token_stream($expected, $parser.location()))
         }
     };
 }
 
-pub fn run(tokens: Vec<Token>) -> Result<Ast> {
+pub fn run<'a>(tokens: Vec<Token<'a>>) -> result::Result<Ast<'a>, ExclaimError> {
     let mut parser = Parser::new(tokens);
-    parse(&mut parser)
+    parse(&mut parser).map_err(|errors| into_exclaim_error(errors, None))
 }
 
-fn parse(parser: &mut Parser) -> Result<Ast> {
+/// Same as run, but the error messages include the line of the source where the error occurred
+pub fn run_with_source<'a>(tokens: Vec<Token<'a>>, source: &str) -> result::Result<Ast<'a>, ExclaimError> {
+    let mut parser = Parser::new(tokens);
+    parse(&mut parser).map_err(|errors| into_exclaim_error(errors, Some(source)))
+}
+
+/// A single error is reported as is, several errors are reported together as ExclaimError::Multiple
+fn into_exclaim_error(errors: Vec<ParserError>, source: Option<&str>) -> ExclaimError {
+    let mut errors: Vec<ExclaimError> = errors.into_iter()
+        .map(|e| {
+            // Every collected error has a location, see parse()
+            let location = e.location().unwrap_or_default();
+            match source {
+                Some(source) => {
+                    let line = location.debug_line(source, &e.underline());
+                    ExclaimError::Parse(location, format!("{} On line [{}; {}]:\n\t{}", e, location.line(), location.column(), line))
+                },
+                None => ExclaimError::Parse(location, e.to_string()),
+            }
+        })
+        .collect();
+
+    if errors.len() == 1 {
+        errors.remove(0)
+    } else {
+        ExclaimError::Multiple(errors)
+    }
+}
+
+/// Parses every block of the token stream. 
+/// When a block fails to parse, the error is collected and the parser resynchronizes at the next block, 
+/// so every error of the template is reported at once.
+fn parse<'a>(parser: &mut Parser<'a>) -> result::Result<Ast<'a>, Vec<ParserError>> {
     let mut ast = Ast::new();
     let mut last_idx: Option<AstIndex> = None;
+    let mut errors: Vec<ParserError> = vec![];
 
     while !parser.end_of_token_stream() {
-        let new_idx = parse_block(parser, &mut ast)?;
+        let remaining = parser.remaining();
+        let new_idx = match parse_block(parser, &mut ast) {
+            Ok(new_idx) => new_idx,
+            Err(e) => {
+                errors.push(e.or_location(parser.location()));
+                parser.synchronize(remaining);
+                continue;
+            },
+        };
+
         match last_idx {
             Some(idx) => {
                 // Get last block so we can set next to current new block_idx
-                let last_block = ast.get(idx);
-                if let AstElement::Block(_, block) = &mut *last_block.borrow_mut() {
+                if let AstElement::Block(_, block) = ast.get_mut(idx) {
                     block.set_next(new_idx);
                     last_idx = Some(new_idx);
                 } else {
-                    return Err(ParserError::from("Parser<parse>: last_idx does not point to a Block element."));
+                    errors.push(ParserError::from("Parser<parse>: last_idx does not point to a Block element.").or_location(parser.location()));
+                    return Err(errors);
                 };
             }
             None => {
@@ -72,297 +184,534 @@ fn parse(parser: &mut Parser) -> Result<Ast> {
         }
     }
 
-    Ok(ast)
+    if errors.is_empty() {
+        Ok(ast)
+    } else {
+        Err(errors)
+    }
 }
 
-fn parse_block(parser: &mut Parser, ast: &mut Ast) -> Result<AstIndex> {
-    let token = unwrap_token!(parser.peek());
+fn parse_block<'a>(parser: &mut Parser<'a>, ast: &mut Ast<'a>) -> Result<AstIndex> {
+    let start = parser.location();
+    let token = unwrap_token!(parser, "text or a code block");
     match token {
         Token::StringLiteral(_, _) => {
             let text_block = Block::Text(parser.consume(), None);
-            let index = ast.push(text_block);
+            let index = parser.push_spanned(ast, start, text_block);
             Ok(index)
         },
         _ => parse_block_code(parser, ast)
     }
 }
 
-fn parse_block_code(parser: &mut Parser, ast: &mut Ast) -> Result<AstIndex> {
-    let token = unwrap_token!(parser.peek());
+fn parse_block_code<'a>(parser: &mut Parser<'a>, ast: &mut Ast<'a>) -> Result<AstIndex> {
+    let start = parser.location();
+    let token = unwrap_token!(parser, "'{{' to open a code block");
     let _block_open = match token {
-        Token::Operator(op, _) => {
-            match op {
-                Op::BlockOpen => parser.consume(),
-                _ => return Err(ParserError::from("Expected Operator(BlockOpen)")),
-            }
-        },
-        _ => return Err(ParserError::from("Expected Operator(BlockOpen)")),
+        Token::Operator(Op::BlockOpen, _) => parser.consume(),
+        _ => return Err(ParserError::unexpected("'{{' to open a code block", token)),
     };
 
     let statement_idx = parse_statement(parser, ast)?;
 
-    let token = unwrap_token!(parser.peek());
+    let token = unwrap_token!(parser, "'}}' to close the code block");
     let _block_close = match token {
-        Token::Operator(op, _) => {
-            match op {
-                Op::BlockClose => parser.consume(),
-                _ => return Err(ParserError::from("Expected Operator(BlockClose)")),
-            }
-        },
-        _ => return Err(ParserError::from("Expected Operator(BlockClose)")),
+        Token::Operator(Op::BlockClose, _) => parser.consume(),
+        _ => return Err(ParserError::unexpected("'}}' to close the code block", token)),
     };
 
     // Derive the type of block by the statement
     // TODO-ALPHA this code displays a design flaw in the data structures; highlights the unclear relationship between blocks and statements.
-    let statement_cell = ast.get(statement_idx);
-    let block = if let AstElement::Statement(_, statement) = &*statement_cell.borrow_mut() {
+    let block = if let AstElement::Statement(_, statement) = ast.get(statement_idx) {
         match statement {
             Statement::End(_) => Block::CodeClosing(statement_idx, None),
             Statement::Let(_, _, _) => Block::CodeEnclosed(statement_idx, None),
             Statement::Render(_, _, _) => Block::CodeUnclosed(statement_idx, vec![], None), // Scope is filled in during semantic analysis
-            Statement::Write(_, _) => Block::CodeEnclosed(statement_idx, None)
+            Statement::Write(_, _) => Block::CodeEnclosed(statement_idx, None),
+            Statement::Include(_, _) => Block::CodeEnclosed(statement_idx, None),
+            Statement::Extends(_, _) => Block::CodeEnclosed(statement_idx, None),
+            Statement::Block(_, _) => Block::CodeUnclosed(statement_idx, vec![], None), // Scope is filled in during semantic analysis
+            Statement::Super(_) => Block::CodeEnclosed(statement_idx, None),
+            // Every branch is a scope, closed by the next branch or {{!}}
+            Statement::If(_, _) => Block::CodeUnclosed(statement_idx, vec![], None),
+            Statement::Elif(_, _) => Block::CodeUnclosed(statement_idx, vec![], None),
+            Statement::Else(_) => Block::CodeUnclosed(statement_idx, vec![], None),
         }
     } else {
-        return Err(ParserError::from("Expected to fetch a statement to derive the block type."));
+        return Err(ParserError::from("Expected to fetch a statement to derive the block type.").or_location(parser.location()));
     };
 
-    Ok(ast.push(block))
+    Ok(parser.push_spanned(ast, start, block))
 }
 
-fn parse_statement(parser: &mut Parser, ast: &mut Ast) -> Result<AstIndex> {
-    let token = unwrap_token!(parser.peek());
+fn parse_statement<'a>(parser: &mut Parser<'a>, ast: &mut Ast<'a>) -> Result<AstIndex> {
+    let start = parser.location();
+    let token = unwrap_token!(parser, "an action to start the code block");
     match token {
         Token::Action(action, _) => {
             match action {
                 Action::End => {
                     let action = parser.consume();
                     let statement = Statement::End(action);
-                    Ok(ast.push(statement))
+                    Ok(parser.push_spanned(ast, start, statement))
                 },
                 Action::Let => {
                     let action = parser.consume();
                     let pattern = parse_pattern_decleration(parser, ast)?;
 
                     // Parse Operator(assign)
-                    let token = unwrap_token!(parser.peek());
+                    let token = unwrap_token!(parser, "'=' to assign an expression in the let! statement");
                     let _assign = match token {
-                        Token::Operator(op, _) => {
-                            match op {
-                                Op::Assign => parser.consume(),
-                                _ => return Err(ParserError::from("Expected assign operator.")),
-                            }
-                        },
-                        _ => return Err(ParserError::from("Expected Operator(Assign).")),
+                        Token::Operator(Op::Assign, _) => parser.consume(),
+                        _ => return Err(ParserError::unexpected("'=' to assign an expression in the let! statement", token)),
                     };
 
                     let expression = parse_expression(parser, ast)?;
                     let statement = Statement::Let(action, pattern, expression);
-                    Ok(ast.push(statement))
+                    Ok(parser.push_spanned(ast, start, statement))
                 },
                 Action::Render => {
                     let action = parser.consume();
                     let pattern = parse_pattern_decleration(parser, ast)?;
                 
                     // Parse Operator(each)
-                    let token = unwrap_token!(parser.peek());
+                    let token = unwrap_token!(parser, "':' to iterate over an expression in the render! statement");
                     let _each = match token {
-                        Token::Operator(op, _) => {
-                            match op {
-                                Op::Each => parser.consume(),
-                                _ => return Err(ParserError::from("Expected each operator.")),
-                            }
-                        },
-                        _ => return Err(ParserError::from("Expected Operator(Each)"))
+                        Token::Operator(Op::Each, _) => parser.consume(),
+                        _ => return Err(ParserError::unexpected("':' to iterate over an expression in the render! statement", token)),
                     };
 
                     let expression = parse_expression(parser, ast)?;
                     let statement = Statement::Render(action, pattern, expression);
-                    Ok(ast.push(statement))
+                    Ok(parser.push_spanned(ast, start, statement))
                 },
                 Action::Write => {
                     let action = parser.consume();
                     let expression = parse_expression(parser, ast)?;
                     let statement = Statement::Write(action, expression);
-                    Ok(ast.push(statement))
+                    Ok(parser.push_spanned(ast, start, statement))
+                },
+                Action::Include => {
+                    let action = parser.consume();
+                    let template_name = parse_expression(parser, ast)?;
+                    let statement = Statement::Include(action, template_name);
+                    Ok(parser.push_spanned(ast, start, statement))
+                },
+                Action::Extends => {
+                    let action = parser.consume();
+                    let template_name = parse_expression(parser, ast)?;
+                    let statement = Statement::Extends(action, template_name);
+                    Ok(parser.push_spanned(ast, start, statement))
+                },
+                Action::Block => {
+                    let action = parser.consume();
+
+                    let token = unwrap_token!(parser, "a label to name the block! region");
+                    let name = match token {
+                        Token::Label(_, _) => parser.consume(),
+                        _ => return Err(ParserError::unexpected("a label to name the block! region", token)),
+                    };
+
+                    let statement = Statement::Block(action, name);
+                    Ok(parser.push_spanned(ast, start, statement))
+                },
+                Action::Super => {
+                    let action = parser.consume();
+                    let statement = Statement::Super(action);
+                    Ok(parser.push_spanned(ast, start, statement))
+                },
+                Action::If => {
+                    let action = parser.consume();
+                    let condition = parse_expression(parser, ast)?;
+                    let statement = Statement::If(action, condition);
+                    Ok(parser.push_spanned(ast, start, statement))
+                },
+                Action::Elif => {
+                    let action = parser.consume();
+                    let condition = parse_expression(parser, ast)?;
+                    let statement = Statement::Elif(action, condition);
+                    Ok(parser.push_spanned(ast, start, statement))
+                },
+                Action::Else => {
+                    let action = parser.consume();
+                    let statement = Statement::Else(action);
+                    Ok(parser.push_spanned(ast, start, statement))
                 }
             }
         },
-        _ => return Err(ParserError::from("Expected Action to start in Block")),
+        _ => Err(ParserError::unexpected("an action to start the code block", token)),
+    }
+}
+
+fn parse_expression<'a>(parser: &mut Parser<'a>, ast: &mut Ast<'a>) -> Result<AstIndex> {
+    parse_binary_expression(parser, ast, 0)
+}
+
+/// Binding power of a binary operator, operators with a higher precedence bind tighter
+fn binary_precedence(token: &Token) -> Option<u8> {
+    match token.operator()? {
+        Op::Or => Some(1),
+        Op::And => Some(2),
+        Op::Equality | Op::Inequality => Some(3),
+        Op::Less | Op::LessEqual | Op::Greater | Op::GreaterEqual => Some(4),
+        Op::Add | Op::Subtract => Some(5),
+        Op::Multiply | Op::Divide | Op::Remainder => Some(6),
+        _ => None,
+    }
+}
+
+/// Precedence climbing: parses the operands joined by operators that bind tighter than min_precedence. 
+/// Operators of the same precedence are left associative.
+fn parse_binary_expression<'a>(parser: &mut Parser<'a>, ast: &mut Ast<'a>, min_precedence: u8) -> Result<AstIndex> {
+    let start = parser.location();
+    let mut lhs = parser.nested(|parser| parse_unary_expression(parser, ast))?;
+
+    loop {
+        let precedence = match parser.peek().and_then(binary_precedence) {
+            Some(precedence) if precedence > min_precedence => precedence,
+            _ => break,
+        };
+
+        let operator = parser.consume();
+        let rhs = parser.nested(|parser| parse_binary_expression(parser, ast, precedence))?;
+        let expression = Expression::Binary(operator, lhs, rhs);
+        lhs = parser.push_spanned(ast, start, expression);
+    }
+
+    Ok(lhs)
+}
+
+fn parse_unary_expression<'a>(parser: &mut Parser<'a>, ast: &mut Ast<'a>) -> Result<AstIndex> {
+    let start = parser.location();
+    let token = unwrap_token!(parser, "an expression: a reference or a literal");
+    match token {
+        Token::Operator(Op::Not, _) | Token::Operator(Op::Subtract, _) => {
+            let operator = parser.consume();
+
+            // A negated number is a negative literal, so the transforms of the literal apply to the negative number 
+            if let (Some(Op::Subtract), Some(Token::NumberLiteral(_, _))) = (operator.operator(), parser.peek()) {
+                let literal = negative_literal(&operator, parser.consume())?;
+                let transforms = parse_tranforms(parser, ast)?;
+                let expression = Expression::Literal(literal, transforms);
+                return Ok(parser.push_spanned(ast, start, expression));
+            }
+
+            let operand = parser.nested(|parser| parse_unary_expression(parser, ast))?;
+            let expression = Expression::Unary(operator, operand);
+            Ok(parser.push_spanned(ast, start, expression))
+        },
+        _ => parser.nested(|parser| parse_operand(parser, ast)),
+    }
+}
+
+/// The number literal negated by the '-' in front of it, spanning both tokens
+fn negative_literal<'a>(sign: &Token<'a>, literal: Token<'a>) -> Result<Token<'a>> {
+    let number = match literal.number_literal() {
+        Some(Number::Uint(uint)) => isize::try_from(-(*uint as i128)).ok().map(Number::Int),
+        Some(Number::Int(int)) => int.checked_neg().map(Number::Int),
+        Some(Number::Float(float)) => Some(Number::Float(-float)),
+        None => None,
+    };
+
+    match number {
+        Some(number) => Ok(Token::NumberLiteral(number, sign.span().to(literal.span()))),
+        None => Err(ParserError::unexpected("a negative number that fits in a signed integer", &literal)),
     }
 }
 
-fn parse_expression(parser: &mut Parser, ast: &mut Ast) -> Result<AstIndex> {
-    let token = unwrap_token!(parser.peek());
+/// A literal, a reference, a parenthesized expression or a collection literal, followed by its transforms
+fn parse_operand<'a>(parser: &mut Parser<'a>, ast: &mut Ast<'a>) -> Result<AstIndex> {
+    let start = parser.location();
+    let token = unwrap_token!(parser, "an expression: a reference or a literal");
     match token {
         Token::StringLiteral(_, _) => {
             let literal = parser.consume();
             let transforms = parse_tranforms(parser, ast)?;
             let expression = Expression::Literal(literal, transforms);
-            Ok(ast.push(expression))
+            Ok(parser.push_spanned(ast, start, expression))
         },
-        Token::NumberLiteral(_, _) => {
+        Token::NumberLiteral(_, _) | Token::BoolLiteral(_, _) | Token::NullLiteral(_) => {
             let literal = parser.consume();
             let transforms = parse_tranforms(parser, ast)?;
             let expression = Expression::Literal(literal, transforms);
-            Ok(ast.push(expression))
+            Ok(parser.push_spanned(ast, start, expression))
         },
         Token::Label(_, _) => {
-            let mut ref_list = vec![parser.consume()];
-            
-            // Collect dot operated references
-            loop {
-                // Check for dot operator
-                let token = unwrap_token!(parser.peek());
-                match token {
-                    Token::Operator(op, _) => {
-                        match op {
-                            Op::Dot => {
-                                // Expect a label token 
-                                let _dot = parser.consume();
-
-                                let token = unwrap_token!(parser.peek());
-                                let label = match token {
-                                    Token::Label(_, _) => parser.consume(),
-                                    _ => return Err(ParserError::from("Expected a label after dot operator"))
-                                };
-
-                                ref_list.push(label);
-                            },
-                            _ => break,
-                        }
-                    }
-                    _ => break,
-                }
-            }
+            let mut accessors = vec![Access::Member(parser.consume())];
+            accessors.append(&mut parse_accessors(parser, ast)?);
 
             let transforms = parse_tranforms(parser, ast)?;
-            let expression = Expression::Reference(ref_list, transforms);
-            Ok(ast.push(expression))
+            let expression = Expression::Reference(accessors, transforms);
+            Ok(parser.push_spanned(ast, start, expression))
+        },
+        Token::Operator(Op::ParenOpen, _) => {
+            let _paren_open = parser.consume();
+
+            // () is the empty tuple
+            let token = unwrap_token!(parser, "an expression or ')' to close the tuple");
+            if let Token::Operator(Op::ParenClose, _) = token {
+                let _paren_close = parser.consume();
+                return parse_operand_suffix(parser, ast, start, |transforms| Expression::Tuple(vec![], transforms));
+            }
+
+            // A comma after the first expression makes the parentheses a tuple, e.g. (a,) or (a, b)
+            let first = parser.nested(|parser| parse_expression(parser, ast))?;
+            let token = unwrap_token!(parser, "',' or ')' to continue the tuple or close the parentheses");
+            match token {
+                Token::Operator(Op::ParenClose, _) => {
+                    let _paren_close = parser.consume();
+                    parse_operand_suffix(parser, ast, start, |transforms| Expression::Group(first, transforms))
+                },
+                Token::Operator(Op::Comma, _) => {
+                    let _comma = parser.consume();
+                    let mut elements = vec![first];
+                    elements.append(&mut parse_expression_list(parser, ast, Op::ParenClose, "',' or ')' to continue or close the tuple")?);
+                    parse_operand_suffix(parser, ast, start, |transforms| Expression::Tuple(elements, transforms))
+                },
+                _ => Err(ParserError::unexpected("',' or ')' to continue the tuple or close the parentheses", token)),
+            }
+        },
+        Token::Operator(Op::ClosureOpen, _) => {
+            let _bracket_open = parser.consume();
+            let elements = parse_expression_list(parser, ast, Op::ClosureClose, "',' or ']' to continue or close the array")?;
+            parse_operand_suffix(parser, ast, start, |transforms| Expression::Array(elements, transforms))
         },
-        _ => return Err(ParserError::from("Expected expressions: Reference, StringLiteral, NumberLiteral")),
+        Token::Operator(Op::BlockOpenPrime, _) => {
+            let _brace_open = parser.consume();
+            let members = parse_object_members(parser, ast)?;
+            parse_operand_suffix(parser, ast, start, |transforms| Expression::Object(members, transforms))
+        },
+        _ => Err(ParserError::unexpected("an expression: a reference or a literal", token)),
     }
 }
 
-fn parse_tranforms(parser: &mut Parser, ast: &mut Ast) -> Result<Vec<AstIndex>> {
+/// Collects the '.label' and '[index]' accessors following an operand
+fn parse_accessors<'a>(parser: &mut Parser<'a>, ast: &mut Ast<'a>) -> Result<Vec<Access<'a>>> {
+    let mut accessors: Vec<Access> = vec![];
+
+    loop {
+        let token = unwrap_token!(parser, "'}}' to close the code block");
+        match token {
+            Token::Operator(Op::Dot, _) => {
+                // Expect a label token 
+                let _dot = parser.consume();
+
+                let token = unwrap_token!(parser, "a label after the dot operator");
+                let label = match token {
+                    Token::Label(_, _) => parser.consume(),
+                    _ => return Err(ParserError::unexpected("a label after the dot operator", token))
+                };
+
+                accessors.push(Access::Member(label));
+            },
+            Token::Operator(Op::ClosureOpen, _) => {
+                let _bracket_open = parser.consume();
+                let index = parse_expression(parser, ast)?;
+
+                let token = unwrap_token!(parser, "']' to close the index");
+                let _bracket_close = match token {
+                    Token::Operator(Op::ClosureClose, _) => parser.consume(),
+                    _ => return Err(ParserError::unexpected("']' to close the index", token)),
+                };
+
+                accessors.push(Access::Index(index));
+            },
+            _ => break,
+        }
+    }
+
+    Ok(accessors)
+}
+
+/// Pushes the parenthesized expression or collection literal followed by its accessors and transforms. 
+/// With accessors, the transforms apply to the accessed value instead of the operand.
+fn parse_operand_suffix<'a>(parser: &mut Parser<'a>, ast: &mut Ast<'a>, start: Location, operand: impl FnOnce(Vec<AstIndex>) -> Expression<'a>) -> Result<AstIndex> {
+    let accessors = parse_accessors(parser, ast)?;
+    let transforms = parse_tranforms(parser, ast)?;
+    if accessors.is_empty() {
+        return Ok(parser.push_spanned(ast, start, operand(transforms)));
+    }
+
+    let operand = parser.push_spanned(ast, start, operand(vec![]));
+    let expression = Expression::Access(operand, accessors, transforms);
+    Ok(parser.push_spanned(ast, start, expression))
+}
+
+/// Parses comma separated expressions up to and including the close operator. A trailing comma is allowed.
+fn parse_expression_list<'a>(parser: &mut Parser<'a>, ast: &mut Ast<'a>, close: Op, expected: &str) -> Result<Vec<AstIndex>> {
+    let mut expressions: Vec<AstIndex> = vec![];
+
+    loop {
+        let token = unwrap_token!(parser, expected);
+        if token.operator() == Some(&close) {
+            let _close = parser.consume();
+            break;
+        }
+
+        expressions.push(parser.nested(|parser| parse_expression(parser, ast))?);
+
+        // Check if next token is a comma or the close operator
+        let token = unwrap_token!(parser, expected);
+        match token {
+            Token::Operator(Op::Comma, _) => {
+                let _comma = parser.consume();
+            },
+            Token::Operator(op, _) if *op == close => {
+                let _close = parser.consume();
+                break;
+            },
+            _ => return Err(ParserError::unexpected(expected, token)),
+        }
+    }
+
+    Ok(expressions)
+}
+
+/// Parses the key: value members of an object literal up to and including the closing brace. A trailing comma is allowed.
+fn parse_object_members<'a>(parser: &mut Parser<'a>, ast: &mut Ast<'a>) -> Result<Vec<(Token<'a>, AstIndex)>> {
+    let mut members: Vec<(Token<'a>, AstIndex)> = vec![];
+
+    loop {
+        let token = unwrap_token!(parser, "a key or '}' to close the object");
+        let key = match token {
+            Token::Operator(Op::BlockClosePrime, _) => {
+                let _brace_close = parser.consume();
+                break;
+            },
+            Token::Label(_, _) | Token::StringLiteral(_, _) => parser.consume(),
+            _ => return Err(ParserError::unexpected("a label or a string as the key of the object member", token)),
+        };
+
+        let name = object_key(&key);
+        if members.iter().any(|(member, _)| object_key(member) == name) {
+            return Err(ParserError::duplicate_key(name, &key));
+        }
+
+        let token = unwrap_token!(parser, "':' after the key of the object member");
+        let _colon = match token {
+            Token::Operator(Op::Each, _) => parser.consume(),
+            _ => return Err(ParserError::unexpected("':' after the key of the object member", token)),
+        };
+
+        let value = parser.nested(|parser| parse_expression(parser, ast))?;
+        members.push((key, value));
+
+        // Check if next token is a comma or a closing brace
+        let token = unwrap_token!(parser, "',' or '}' to continue or close the object");
+        match token {
+            Token::Operator(Op::Comma, _) => {
+                let _comma = parser.consume();
+            },
+            Token::Operator(Op::BlockClosePrime, _) => {
+                let _brace_close = parser.consume();
+                break;
+            },
+            _ => return Err(ParserError::unexpected("',' or '}' to continue or close the object", token)),
+        }
+    }
+
+    Ok(members)
+}
+
+/// Name of the object member, keys are labels or string literals
+fn object_key<'k>(key: &'k Token) -> &'k str {
+    key.label().or_else(|| key.string_literal()).unwrap_or_default()
+}
+
+fn parse_tranforms<'a>(parser: &mut Parser<'a>, ast: &mut Ast<'a>) -> Result<Vec<AstIndex>> {
     let mut transforms: Vec<AstIndex> = vec![];
 
     // collect as many transforms as possible 
     loop {
-        let token = unwrap_token!(parser.peek());
+        let token = unwrap_token!(parser, "'}}' to close the code block");
         let _pipe = match token {
-            Token::Operator(op, _) => {
-                match op {
-                    Op::Pipe => parser.consume(), // Pipe operator |
-                    _ => break,
-                }
-            }
+            Token::Operator(Op::Pipe, _) => parser.consume(), // Pipe operator |
             _ => break,
         };
         
-        let token = unwrap_token!(parser.peek());
+        let start = parser.location();
+        let token = unwrap_token!(parser, "a transform label after the pipe operator");
         let label = match token {
             Token::Label(_, _) => parser.consume(), // Label
-            _ => return Err(ParserError::from("Expected transform label after Pipe Operator.")),
+            _ => return Err(ParserError::unexpected("a transform label after the pipe operator", token)),
         };
 
         // Parse arguments
         let mut arguments: Vec<AstIndex> = vec![];
-        let token = unwrap_token!(parser.peek());
-        match token {
-            Token::Operator(op, _) => {
-                match op {
-                    Op::ParenOpen => {
-                        let _paren_open = parser.consume(); // Paren open (
-                        
-                        // Parse argument list
-                        loop {
-                            let argument = parse_expression(parser, ast)?;
-                            arguments.push(argument);
-
-                            // Check if next token is a comma or an close parenthesis
-                            let token = unwrap_token!(parser.peek());
-                            match token {
-                                Token::Operator(op, _) => {
-                                    match op {
-                                        Op::Comma => {
-                                            let _comma = parser.consume();
-                                            continue; // More arguments to parse!
-                                        },
-                                        Op::ParenClose => {
-                                            let _close_paren = parser.consume();
-                                            break; // End of argument list 
-                                        },
-                                        _ => return Err(ParserError::from("Expected a comma or close parenthesis to complete an argument list."))
-                                    }
-                                },
-                                _ => return Err(ParserError::from("Expected a comma or close parenthesis to complete an argument list."))
-                            }
-                        }
+        let token = unwrap_token!(parser, "'}}' to close the code block");
+        if let Token::Operator(Op::ParenOpen, _) = token {
+            let _paren_open = parser.consume(); // Paren open (
+            
+            // Parse argument list
+            loop {
+                let argument = parse_expression(parser, ast)?;
+                arguments.push(argument);
+
+                // Check if next token is a comma or an close parenthesis
+                let token = unwrap_token!(parser, "',' or ')' to continue or complete the argument list");
+                match token {
+                    Token::Operator(Op::Comma, _) => {
+                        let _comma = parser.consume();
+                        continue; // More arguments to parse!
+                    },
+                    Token::Operator(Op::ParenClose, _) => {
+                        let _close_paren = parser.consume();
+                        break; // End of argument list 
                     },
-                    _ => (), // do nothing
+                    _ => return Err(ParserError::unexpected("',' or ')' to continue or complete the argument list", token))
                 }
-            },
-            _ => (), // do nothing
+            }
         }
         
         
         // Create transform and add to list of transforms 
         let transform = Transform::new(label, arguments);
-        let index = ast.push(transform);
+        let index = parser.push_spanned(ast, start, transform);
         transforms.push(index);
     }
 
     Ok(transforms)
 }
 
-fn parse_pattern_decleration(parser: &mut Parser, ast: &mut Ast) -> Result<AstIndex> {
+fn parse_pattern_decleration<'a>(parser: &mut Parser<'a>, ast: &mut Ast<'a>) -> Result<AstIndex> {
+    let start = parser.location();
     // Parse Pattern 
-    let token = unwrap_token!(parser.peek());
+    let token = unwrap_token!(parser, "a decleration pattern: a label, or labels separated by commas inside of parentheses");
     let decls = match token {
         Token::Label(_, _) => vec![parser.consume()],
-        Token::Operator(op, _) => {
-            match op {
-                Op::ParenOpen => {
-                    let _open_paren = parser.consume();
-
-                    // Parse declerations 
-                    let mut decls: Vec<Token> = vec![];
-                    loop {
-                        let token = unwrap_token!(parser.peek());
-                        let decl = match token {
-                            Token::Label(_, _) => parser.consume(),
-                            _ => return Err(ParserError::from("Expected label for decleration in Pattern"))
-                        };
-
-                        decls.push(decl);
-
-                        // Determine if end of pattern or more declerations to parse
-                        let token = unwrap_token!(parser.peek());
-                        match token {
-                            Token::Operator(op, _) => {
-                                match op {
-                                    Op::Comma => {
-                                        let _comma = parser.consume();
-                                        continue; // More declerations!
-                                    },
-                                    Op::ParenClose => {
-                                        let _close_paren = parser.consume();
-                                        break; // End of pattern
-                                    }
-                                    _ => return Err(ParserError::from("Expected either a Close Parenthesis to end the Pattern, or a comma to continue the pattern."))
-                                }
-                            },
-                            _ => return Err(ParserError::from("Expected either a Close Parenthesis to end the Pattern, or a comma to continue the pattern."))
-                        }
-                    }
+        Token::Operator(Op::ParenOpen, _) => {
+            let _open_paren = parser.consume();
 
-                    decls
-                },
-                _ => return Err(ParserError::from("Expected either a Close Parenthesis to end the Pattern, or a comma to continue the pattern."))
+            // Parse declerations 
+            let mut decls: Vec<Token<'a>> = vec![];
+            loop {
+                let token = unwrap_token!(parser, "a label for the decleration in the pattern");
+                let decl = match token {
+                    Token::Label(_, _) => parser.consume(),
+                    _ => return Err(ParserError::unexpected("a label for the decleration in the pattern", token))
+                };
+
+                decls.push(decl);
+
+                // Determine if end of pattern or more declerations to parse
+                let token = unwrap_token!(parser, "',' or ')' to continue or end the pattern");
+                match token {
+                    Token::Operator(Op::Comma, _) => {
+                        let _comma = parser.consume();
+                        continue; // More declerations!
+                    },
+                    Token::Operator(Op::ParenClose, _) => {
+                        let _close_paren = parser.consume();
+                        break; // End of pattern
+                    }
+                    _ => return Err(ParserError::unexpected("',' or ')' to continue or end the pattern", token))
+                }
             }
+
+            decls
         }
-        _ => return Err(ParserError::from("Expected a Decleration Pattern to assign an expression to in a let! statement."))
+        _ => return Err(ParserError::unexpected("a decleration pattern: a label, or labels separated by commas inside of parentheses", token))
     };
     let pattern = Pattern::Decleration(decls);
-    Ok(ast.push(pattern))
+    Ok(parser.push_spanned(ast, start, pattern))
 }
\ No newline at end of file
@@ -1,36 +1,199 @@
 use crate::ast::prelude::*;
+use crate::common::{
+    ExclaimError,
+    Location,
+};
 use crate::data::traits::Renderable;
 use crate::data::DataContext;
 use crate::data::Data;
+use crate::environment::Environment;
+use crate::tokens::{
+    Op,
+    Token,
+};
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::rc::Rc;
 
 mod scope;
 use scope::ScopeContext;
 
+mod sink;
+pub use sink::IoSink;
+
+#[allow(clippy::module_inception)]
 mod runtime;
-use runtime::RuntimeContext;
+use runtime::{
+    BlockFrame,
+    BlockRegion,
+    RuntimeContext,
+};
+
+type RuntimeResult<T> = Result<T, ExclaimError>;
+
+pub fn run(ast: &Ast, data: Option<&DataContext>) -> RuntimeResult<String> {
+    let mut output = String::new();
+    run_to(ast, data, &mut output)?;
+    Ok(output)
+}
 
+/// Renders the ast directly into the sink
+pub fn run_to(ast: &Ast, data: Option<&DataContext>, sink: &mut dyn fmt::Write) -> RuntimeResult<()> {
+    let mut runtime = RuntimeContext::new(data, sink);
+    run_template(ast, &mut runtime)
+}
 
-pub fn run(mut ast: Ast, data: Option<DataContext>) -> Result<String, String> {
-    let mut runtime = RuntimeContext::new(data);
+/// Renders the named template of the environment into the sink. Templates rendered this way can use include!
+pub fn run_environment_to(env: &Environment, name: &str, data: Option<&DataContext>, sink: &mut dyn fmt::Write) -> RuntimeResult<()> {
+    let template = match env.get_template(name) {
+        Some(template) => template,
+        None => return Err(ExclaimError::Runtime(Location::default(), format!("Template '{}' is not registered in the environment.", name))),
+    };
+
+    let mut runtime = RuntimeContext::new(data, sink).with_environment(env);
+    runtime.push_include(name.to_string());
+
+    // On error, the include chain is left as it was when the error occurred 
+    run_template(template.ast(), &mut runtime).map_err(|e| {
+        let chain = runtime.include_chain();
+        if chain.len() > 1 {
+            let chain = chain.join(" -> ");
+            e.map_message(|msg| format!("{} (include chain: {})", msg, chain))
+        } else {
+            e
+        }
+    })
+}
 
+/// Renders a template, or the template it extends when it starts with extends!
+fn run_template<'a>(ast: &'a Ast, runtime: &mut RuntimeContext<'a>) -> RuntimeResult<()> {
+    // Gather the file scope extends! statement and block! regions
+    let mut extends: Option<AstIndex> = None;
+    let mut regions: Vec<(String, AstIndex)> = vec![];
+    let mut current_block = ast.head();
+    while let Some(block) = current_block {
+        let block_ref = ast.get(block);
+
+        current_block = match block_ref {
+            AstElement::Block(_, Block::CodeEnclosed(statement, next)) => {
+                if let AstElement::Statement(_, Statement::Extends(_, _)) = ast.get(*statement) {
+                    extends = Some(*statement);
+                }
+                *next
+            },
+            AstElement::Block(_, Block::CodeUnclosed(_, _, next)) => {
+                collect_block_regions(ast, block, &mut regions)?;
+                *next
+            },
+            AstElement::Block(_, Block::Text(_, next)) => *next,
+            AstElement::Block(_, Block::CodeClosing(_, next)) => *next,
+            _ => return Err(ExclaimError::Runtime(ast.span(block).start(), "Expected a block".to_string())),
+        };
+    }
+
+    let extends = match extends {
+        Some(extends) => extends,
+        None => return run_blocks(ast, runtime),
+    };
+
+    // The regions of this template take priority over the regions of the templates it extends
+    for (name, region) in regions {
+        runtime.add_block_override(name, (ast, region));
+    }
+
+    // Everything else in the file scope is discarded, except for declerations which are visible to the parent template
+    let mut current_block = ast.head();
+    while let Some(block) = current_block {
+        let block_ref = ast.get(block);
+
+        current_block = match block_ref {
+            AstElement::Block(_, Block::CodeEnclosed(statement, next)) => {
+                if let AstElement::Statement(_, Statement::Let(_, _, _)) = ast.get(*statement) {
+                    run_statement(ast, runtime, *statement)?;
+                }
+                *next
+            },
+            AstElement::Block(_, Block::CodeUnclosed(_, _, next)) => *next,
+            AstElement::Block(_, Block::Text(_, next)) => *next,
+            AstElement::Block(_, Block::CodeClosing(_, next)) => *next,
+            _ => return Err(ExclaimError::Runtime(ast.span(block).start(), "Expected a block".to_string())),
+        };
+    }
+
+    let statement_ref = ast.get(extends);
+    let (action, template_name) = match statement_ref {
+        AstElement::Statement(_, Statement::Extends(action, template_name)) => (action, *template_name),
+        _ => return Err(ExclaimError::Runtime(ast.span(extends).start(), "Expected an Extends Statement.".to_string())),
+    };
+
+    let name = match run_expression(ast, runtime, template_name)? {
+        Data::String(name) => name,
+        data => return Err(ExclaimError::Runtime(action.location(), format!("extends! expects the name of a template, but found: {:?}", data))),
+    };
+
+    let env = match runtime.environment() {
+        Some(env) => env,
+        None => return Err(ExclaimError::Runtime(action.location(), format!("Unable to extend '{}', extends! is only available when rendering through an Environment.", name))),
+    };
+
+    if runtime.include_chain().contains(&name) {
+        runtime.push_include(name.clone());
+        return Err(ExclaimError::Runtime(action.location(), format!("Extends cycle detected while extending '{}'.", name)));
+    }
+
+    let template = match env.get_template(&name) {
+        Some(template) => template,
+        None => return Err(ExclaimError::Runtime(action.location(), format!("Unable to extend '{}', the template is not registered in the environment.", name))),
+    };
+
+    runtime.push_include(name);
+    run_template(template.ast(), runtime)?;
+    runtime.pop_include();
+
+    Ok(())
+}
+
+/// Collects the block! regions declared by the block, including the ones nested inside of it
+fn collect_block_regions(ast: &Ast, block: AstIndex, regions: &mut Vec<(String, AstIndex)>) -> RuntimeResult<()> {
+    if let AstElement::Block(_, Block::CodeUnclosed(statement, scope, _)) = ast.get(block) {
+        if let AstElement::Statement(_, Statement::Block(action, name)) = ast.get(*statement) {
+            regions.push((block_name(action, name)?.to_string(), block));
+        }
+
+        for nested_block in scope.iter() {
+            collect_block_regions(ast, *nested_block, regions)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The name of a block! region, the parser only accepts a label after block!
+fn block_name<'t>(action: &Token, name: &'t Token) -> RuntimeResult<&'t str> {
+    name.label()
+        .ok_or_else(|| ExclaimError::Runtime(action.location(), format!("block! expects the name of a region, but found '{}'.", name)))
+}
+
+fn run_blocks<'a>(ast: &'a Ast, runtime: &mut RuntimeContext<'a>) -> RuntimeResult<()> {
     let mut current_block = ast.head();
     while current_block.is_some() {
-        current_block = run_block(&mut ast, &mut runtime, current_block)?;
+        current_block = run_block(ast, runtime, current_block)?;
     }
 
-    Ok(runtime.output())
+    Ok(())
 }
 
-fn run_block(ast: &mut Ast, runtime: &mut RuntimeContext, block: Option<AstIndex>) -> Result<Option<AstIndex>, String> {
+fn run_block<'a>(ast: &'a Ast, runtime: &mut RuntimeContext<'a>, block: Option<AstIndex>) -> RuntimeResult<Option<AstIndex>> {
     if let Some(block) = block {
-        let block_cell = ast.get(block);
-        let mut block_ref = block_cell.borrow_mut();
+        let block_ref = ast.get(block);
 
-        match &mut *block_ref {
-            AstElement::Block(_, block) => {
+        match block_ref {
+            AstElement::Block(index, block) => {
                 match block {
                     Block::Text(text, next) => {
-                        runtime.render(text);
+                        runtime.render(text, text.location())?;
                         Ok(*next)
                     },
                     Block::CodeEnclosed(statement, next) => {
@@ -38,13 +201,12 @@ fn run_block(ast: &mut Ast, runtime: &mut RuntimeContext, block: Option<AstIndex
                         Ok(*next)
                     },
                     Block::CodeUnclosed(statement, scope, next) => {
-                        let statement_cell = ast.get(*statement);
-                        let mut statement_ref = statement_cell.borrow_mut();
+                        let statement_ref = ast.get(*statement);
 
-                        match &mut *statement_ref {
+                        match statement_ref {
                             AstElement::Statement(_, statement) => {
                                 match statement {
-                                    Statement::Render(_action, pattern, expression) => {
+                                    Statement::Render(action, pattern, expression) => {
                                         // Left hand side of assignment - build declerations
                                         let pattern = run_pattern(ast, *pattern)?;
                                         
@@ -57,7 +219,7 @@ fn run_block(ast: &mut Ast, runtime: &mut RuntimeContext, block: Option<AstIndex
                                         // Get iterator from Data variant 
                                         for value in values.into_iter() {
                                             // Insert current value for the iteration
-                                            match_pattern(runtime, &pattern, value)?;
+                                            match_pattern(runtime, &pattern, value, action.location())?;
 
                                             // Run iteration
                                             for nested_block in scope.iter() {
@@ -68,13 +230,27 @@ fn run_block(ast: &mut Ast, runtime: &mut RuntimeContext, block: Option<AstIndex
                                         // Close Scope
                                         runtime.close_scope();
                                     },
-                                    _ => return Err("Runtime Error: Expected a Render Statement.".to_string()),
+                                    Statement::If(_, condition) | Statement::Elif(_, condition) => {
+                                        if run_expression(ast, runtime, *condition)?.is_truthy() {
+                                            run_branch(ast, runtime, scope)?;
+                                        } else if let Some(next_branch) = scope.last() {
+                                            // The scope of a branch ends with its closing block or the next elif!/else! branch
+                                            run_block(ast, runtime, Some(*next_branch))?;
+                                        }
+                                    },
+                                    Statement::Else(_) => run_branch(ast, runtime, scope)?,
+                                    Statement::Block(action, name) => {
+                                        // Render the most derived definition of the region, the others are reachable through super!
+                                        let mut chain = runtime.block_overrides(block_name(action, name)?);
+                                        chain.push((ast, *index));
+                                        run_block_region(runtime, BlockFrame(Rc::from(chain), 0))?;
+                                    },
+                                    _ => return Err(ExclaimError::Runtime(statement.action().location(), "Expected a statement that opens a scope.".to_string())),
                                 }
                             },
-                            _ => return Err("Runtime Error: Expected a statement.".to_string()),
+                            _ => return Err(ExclaimError::Runtime(ast.span(*statement).start(), "Expected a statement.".to_string())),
                         }
-                        
-                        println!("next is : {:?}", next);
+
                         Ok(*next)
                     }
                     Block::CodeClosing(_statement, next) => {
@@ -82,26 +258,62 @@ fn run_block(ast: &mut Ast, runtime: &mut RuntimeContext, block: Option<AstIndex
                     },
                 }
             }
-            _ => Err("Runtime Error: Expected a block".to_string()),
+            _ => Err(ExclaimError::Runtime(ast.span(block).start(), "Expected a block".to_string())),
         }
     } else {
-        Err("Runtime Error: AST ended unexpectedly.".to_string())
+        Err(ExclaimError::Runtime(Location::default(), "AST ended unexpectedly.".to_string()))
+    }
+}
+
+/// Renders the blocks of a branch, without the closing block or the next elif!/else! branch at its end
+fn run_branch<'a>(ast: &'a Ast, runtime: &mut RuntimeContext<'a>, scope: &[AstIndex]) -> RuntimeResult<()> {
+    let blocks = match scope.split_last() {
+        Some((_, blocks)) => blocks,
+        None => scope,
+    };
+
+    runtime.open_scope();
+    for nested_block in blocks.iter() {
+        run_block(ast, runtime, Some(*nested_block))?;
+    }
+    runtime.close_scope();
+
+    Ok(())
+}
+
+/// Renders the definition of the block! region selected by the frame
+fn run_block_region<'a>(runtime: &mut RuntimeContext<'a>, frame: BlockFrame<'a>) -> RuntimeResult<()> {
+    let (ast, region): BlockRegion<'a> = frame.0[frame.1];
+    let block_ref = ast.get(region);
+
+    match block_ref {
+        AstElement::Block(_, Block::CodeUnclosed(_, scope, _)) => {
+            runtime.push_block_frame(frame);
+            runtime.open_scope();
+            for nested_block in scope.iter() {
+                run_block(ast, runtime, Some(*nested_block))?;
+            }
+            runtime.close_scope();
+            runtime.pop_block_frame();
+
+            Ok(())
+        },
+        _ => Err(ExclaimError::Runtime(ast.span(region).start(), "Expected a block! region.".to_string())),
     }
 }
 
-fn run_statement(ast: &mut Ast, runtime: &mut RuntimeContext, statement: AstIndex) -> Result<(), String> {
-    let statement_cell = ast.get(statement);
-    let mut statement_ref = statement_cell.borrow_mut();
+fn run_statement<'a>(ast: &'a Ast, runtime: &mut RuntimeContext<'a>, statement: AstIndex) -> RuntimeResult<()> {
+    let statement_ref = ast.get(statement);
 
-    match &mut *statement_ref {
+    match statement_ref {
         AstElement::Statement(_, statement) => {
             match statement {
-                Statement::Write(_action, expression) => {
+                Statement::Write(action, expression) => {
                     let data = run_expression(ast, runtime, *expression)?;
-                    runtime.render(&data);
+                    runtime.render(&data, action.location())?;
                     Ok(())
                 },
-                Statement::Let(_action, pattern, expression) => {
+                Statement::Let(action, pattern, expression) => {
                     // Left hand side of assignment
                     let pattern = run_pattern(ast, *pattern)?;
 
@@ -109,60 +321,220 @@ fn run_statement(ast: &mut Ast, runtime: &mut RuntimeContext, statement: AstInde
                     let value = run_expression(ast, runtime, *expression)?;
 
                     // Add variables to runtime context
-                    match_pattern(runtime, &pattern, value)?;
+                    match_pattern(runtime, &pattern, value, action.location())?;
 
                     Ok(())
                 },
-                _ => Err("Runtime Error: statement Variant Unimplemented".to_string()),
+                Statement::Include(action, template_name) => {
+                    let name = match run_expression(ast, runtime, *template_name)? {
+                        Data::String(name) => name,
+                        data => return Err(ExclaimError::Runtime(action.location(), format!("include! expects the name of a template, but found: {:?}", data))),
+                    };
+
+                    let env = match runtime.environment() {
+                        Some(env) => env,
+                        None => return Err(ExclaimError::Runtime(action.location(), format!("Unable to include '{}', include! is only available when rendering through an Environment.", name))),
+                    };
+
+                    if runtime.include_chain().contains(&name) {
+                        runtime.push_include(name.clone());
+                        return Err(ExclaimError::Runtime(action.location(), format!("Include cycle detected while including '{}'.", name)));
+                    }
+
+                    let template = match env.get_template(&name) {
+                        Some(template) => template,
+                        None => return Err(ExclaimError::Runtime(action.location(), format!("Unable to include '{}', the template is not registered in the environment.", name))),
+                    };
+
+                    // The included template sees the current scope, but its declerations don't leak out of it
+                    // Its block! regions are not overridden by the templates extending the current one
+                    let overrides = runtime.take_block_overrides();
+                    runtime.push_include(name);
+                    runtime.open_scope();
+                    run_template(template.ast(), runtime)?;
+                    runtime.close_scope();
+                    runtime.pop_include();
+                    runtime.restore_block_overrides(overrides);
+
+                    Ok(())
+                },
+                Statement::Super(action) => {
+                    let frame = match runtime.current_block_frame() {
+                        Some(frame) => frame,
+                        None => return Err(ExclaimError::Runtime(action.location(), "super! can only be used inside of a block! region.".to_string())),
+                    };
+
+                    if frame.1 + 1 >= frame.0.len() {
+                        return Err(ExclaimError::Runtime(action.location(), "super! was used, but the block! region does not override a parent region.".to_string()));
+                    }
+
+                    run_block_region(runtime, BlockFrame(frame.0, frame.1 + 1))
+                },
+                // Handled before the template is rendered
+                Statement::Extends(_, _) => Ok(()),
+                _ => Err(ExclaimError::Runtime(statement.action().location(), "statement Variant Unimplemented".to_string())),
             }
         }
-        _ => Err("Runtime Error: Expected a statement".to_string()),
+        _ => Err(ExclaimError::Runtime(ast.span(statement).start(), "Expected a statement".to_string())),
     }
 }
 
-fn run_expression(ast: &mut Ast, runtime: &mut RuntimeContext, expression: AstIndex) -> Result<Data, String> {
-    let expression_cell = ast.get(expression);
-    let expression_ref = expression_cell.borrow_mut();
+fn run_expression(ast: &Ast, runtime: &mut RuntimeContext, expression: AstIndex) -> RuntimeResult<Data> {
+    let location = ast.span(expression).start();
+    let expression_ref = ast.get(expression);
 
-    if let AstElement::Expression(_, expression) = &*expression_ref {
+    if let AstElement::Expression(_, expression) = expression_ref {
         match expression {
             Expression::Literal(literal, transforms) => {
-                let literal = Data::from(literal.clone());
+                let literal = Data::try_from(literal.clone())?;
                 let literal = run_transformations(ast, runtime, literal, transforms)?;
                 Ok(literal)
             }
-            Expression::Reference(references, transforms) => {
-                // Get the value binded to the initial reference
-                let key = references.get(0).unwrap().label().unwrap();
-                let mut current_reference = runtime.get(key);
-
-                // If there are more references after initial, access the members in sequence
-                // Not exactly a fan of this and could be avoided with better structures 
-                for ref_idx in 1..references.len() {
-                    let key = references.get(ref_idx).unwrap().label().unwrap();
-                    current_reference = current_reference.get(key);
-                }
-
-                // We clone the data, because all transformation happen out of place
-                let reference = current_reference.clone();
+            Expression::Reference(accessors, transforms) => {
+                // The parser starts every reference with the label of the variable
+                let (variable, accessors) = match accessors.split_first() {
+                    Some((Access::Member(variable), accessors)) => (variable, accessors),
+                    _ => return Err(ExclaimError::Runtime(location, "Expected the label of a variable.".to_string())),
+                };
+
+                // Indices are evaluated before the variable is borrowed
+                let keys = run_accessor_keys(ast, runtime, accessors)?;
+                let reference = match runtime.get(variable.label().unwrap_or_default()) {
+                    // Global data may or may not exist, so it is accessed as an option
+                    Some((data, is_global)) => run_access(data, is_global, &keys)?,
+                    None => run_access(&Data::Option(None), false, &keys)?,
+                };
 
                 // Apply transformations
-                let reference = run_transformations(ast, runtime, reference, transforms)?;
+                run_transformations(ast, runtime, reference, transforms)
+            }
+            Expression::Access(operand, accessors, transforms) => {
+                let operand = run_expression(ast, runtime, *operand)?;
+                let keys = run_accessor_keys(ast, runtime, accessors)?;
+                let accessed = run_access(&operand, false, &keys)?;
+                run_transformations(ast, runtime, accessed, transforms)
+            }
+            Expression::Group(expression, transforms) => {
+                let grouped = run_expression(ast, runtime, *expression)?;
+                run_transformations(ast, runtime, grouped, transforms)
+            }
+            Expression::Array(elements, transforms) => {
+                let array = run_expressions(ast, runtime, elements)?;
+                run_transformations(ast, runtime, Data::Array(array), transforms)
+            }
+            Expression::Tuple(elements, transforms) => {
+                let tuple = run_expressions(ast, runtime, elements)?;
+                run_transformations(ast, runtime, Data::Tuple(tuple.into_boxed_slice()), transforms)
+            }
+            Expression::Object(members, transforms) => {
+                let mut object = BTreeMap::new();
+                for (key, value) in members {
+                    // The parser only accepts labels and string literals as keys
+                    let key = key.label().or_else(|| key.string_literal()).unwrap_or_default();
+                    object.insert(key.to_string(), run_expression(ast, runtime, *value)?);
+                }
 
-                Ok(reference)
+                run_transformations(ast, runtime, Data::Object(object), transforms)
             }
+            Expression::Unary(operator, operand) => {
+                let operand = run_expression(ast, runtime, *operand)?;
+                operand.apply_unary_operator(operator)
+            }
+            Expression::Binary(operator, lhs, rhs) => run_binary_expression(ast, runtime, operator, *lhs, *rhs),
         }
     } else {
-        Err("Runtime: Expected an expression".to_string())
+        Err(ExclaimError::Runtime(location, "Expected an expression".to_string()))
+    }
+}
+
+/// An accessor with its index evaluated
+enum AccessKey<'a> {
+    Member(&'a Token<'a>),
+    Index(Data, Location),
+}
+
+fn run_accessor_keys<'a>(ast: &Ast, runtime: &mut RuntimeContext, accessors: &'a [Access]) -> RuntimeResult<Vec<AccessKey<'a>>> {
+    let mut keys: Vec<AccessKey> = vec![];
+    for accessor in accessors {
+        let key = match accessor {
+            Access::Member(label) => AccessKey::Member(label),
+            Access::Index(index) => AccessKey::Index(run_expression(ast, runtime, *index)?, ast.span(*index).start()),
+        };
+        keys.push(key);
+    }
+
+    Ok(keys)
+}
+
+/// Follows the keys from the data, only the accessed value is cloned. 
+/// A member access keeps the option semantics of .label and gives an option of the value, or none when the member doesn't exist. 
+/// An index gives the element itself, so a key or element that doesn't exist is an error at the index.
+fn run_access(data: &Data, is_option: bool, keys: &[AccessKey]) -> RuntimeResult<Data> {
+    let none = Data::Option(None);
+    let mut current = data;
+    let mut is_option = is_option;
+
+    for key in keys {
+        match key {
+            AccessKey::Member(label) => {
+                let accessed = current.member(label.label().unwrap_or_default())
+                    .map_err(|msg| ExclaimError::Runtime(label.location(), msg))?;
+
+                // A missing member is already an empty option, so it is never wrapped again
+                current = accessed.unwrap_or(&none);
+                is_option = accessed.is_some();
+            },
+            AccessKey::Index(index, location) => {
+                current = current.index(index)
+                    .map_err(|msg| ExclaimError::Runtime(*location, msg))?
+                    .ok_or_else(|| ExclaimError::Runtime(*location, missing_index_message(index)))?;
+                is_option = false;
+            },
+        }
+    }
+
+    if is_option {
+        Ok(Data::Option(Some(Box::new(current.clone()))))
+    } else {
+        Ok(current.clone())
+    }
+}
+
+fn missing_index_message(index: &Data) -> String {
+    match index {
+        Data::String(key) => format!("Unable to access the key \"{}\", because it doesn't exist.", key),
+        _ => format!("Unable to access the element at {:?}, because the index is out of range.", index),
     }
 }
 
-fn run_transformations(ast: &mut Ast, runtime: &mut RuntimeContext, mut data: Data, transforms: &Vec<AstIndex>) -> Result<Data, String> {
+fn run_expressions(ast: &Ast, runtime: &mut RuntimeContext, expressions: &[AstIndex]) -> RuntimeResult<Vec<Data>> {
+    let mut values: Vec<Data> = vec![];
+    for expression in expressions {
+        values.push(run_expression(ast, runtime, *expression)?);
+    }
+
+    Ok(values)
+}
+
+/// && and || only evaluate the right hand side when the left hand side doesn't decide the result
+fn run_binary_expression(ast: &Ast, runtime: &mut RuntimeContext, operator: &Token, lhs: AstIndex, rhs: AstIndex) -> RuntimeResult<Data> {
+    let lhs = run_expression(ast, runtime, lhs)?;
+    match operator.operator() {
+        Some(Op::And) if !lhs.is_truthy() => Ok(Data::Bool(false)),
+        Some(Op::Or) if lhs.is_truthy() => Ok(Data::Bool(true)),
+        Some(Op::And) | Some(Op::Or) => Ok(Data::Bool(run_expression(ast, runtime, rhs)?.is_truthy())),
+        _ => {
+            let rhs = run_expression(ast, runtime, rhs)?;
+            lhs.apply_operator(operator, rhs)
+        },
+    }
+}
+
+fn run_transformations(ast: &Ast, runtime: &mut RuntimeContext, mut data: Data, transforms: &[AstIndex]) -> RuntimeResult<Data> {
     for transform in transforms {
-        let transform_cell = ast.get(*transform);
-        let transform_ref = transform_cell.borrow_mut();
+        let transform_ref = ast.get(*transform);
 
-        if let AstElement::Transform(_, transform) = &*transform_ref {
+        if let AstElement::Transform(_, transform) = transform_ref {
             // Get Arguments 
             let mut arguments: Vec<Data> = vec![];
             for argument in transform.arguments() {
@@ -170,7 +542,7 @@ fn run_transformations(ast: &mut Ast, runtime: &mut RuntimeContext, mut data: Da
                 arguments.push(arg);
             }
 
-            data = data.apply_transform(transform, arguments);
+            data = data.apply_transform(transform, arguments)?;
         }
     }
 
@@ -178,36 +550,39 @@ fn run_transformations(ast: &mut Ast, runtime: &mut RuntimeContext, mut data: Da
 }
 
 /// Get declerations from pattern into a vector of strings
-fn run_pattern(ast: &mut Ast, pattern: AstIndex) -> Result<Vec<String>, String> {
-    let pattern_cell = ast.get(pattern);
-    let pattern_ref = pattern_cell.borrow_mut();
+fn run_pattern(ast: &Ast, pattern: AstIndex) -> RuntimeResult<Vec<String>> {
+    let pattern_ref = ast.get(pattern);
 
     let mut declerations: Vec<String> = vec![];
-    match &*pattern_ref {
+    match pattern_ref {
         AstElement::Pattern(_, pat) => {
             match pat {
                 Pattern::Decleration(decls) => {
                     for decl in decls {
-                        declerations.push(decl.label().unwrap().to_string());
+                        match decl.label() {
+                            Some(label) => declerations.push(label.to_string()),
+                            None => return Err(ExclaimError::Runtime(decl.location(), format!("Expected the label of a variable, but found '{}'.", decl))),
+                        }
                     }
                 }
             }
         },
-        _ => return Err("Runtime Error: Let! expected a pattern".to_string()),
+        _ => return Err(ExclaimError::Runtime(ast.span(pattern).start(), "Let! expected a pattern".to_string())),
     };
 
     Ok(declerations)
 }
 
-fn match_pattern(runtime: &mut RuntimeContext, pattern: &Vec<String>, value: Data) -> Result<(), String> {
+/// The location is the action of the let! or render! statement declaring the pattern
+fn match_pattern(runtime: &mut RuntimeContext, pattern: &[String], value: Data, location: Location) -> RuntimeResult<()> {
     if pattern.len() == value.len() || pattern.len() != 1 {
-        for (key, value) in pattern.into_iter().zip(value) {
+        for (key, value) in pattern.iter().zip(value) {
             runtime.insert(key.to_string(), value);
         }
     } else if pattern.len() == 1 {
-        runtime.insert(pattern.get(0).unwrap().to_string(), value)
+        runtime.insert(pattern.first().unwrap().to_string(), value)
     } else {
-        return Err("Runtime Error: Let! expects pattern does not match expression.".to_string());
+        return Err(ExclaimError::Runtime(location, "Let! expects pattern does not match expression.".to_string()));
     }
 
     Ok(())
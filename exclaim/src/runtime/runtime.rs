@@ -1,25 +1,112 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::prelude::{
+    Ast,
+    AstIndex,
+};
+
 use super::ScopeContext;
 use super::DataContext;
+use super::Environment;
 use super::Renderable;
 use super::Data;
+use super::RuntimeResult;
+use super::{
+    ExclaimError,
+    Location,
+};
+
+/// A block! region: the ast it belongs to and the index of its unclosed block
+pub type BlockRegion<'a> = (&'a Ast<'a>, AstIndex);
+
+/// BlockFrame(chain: Rc<[BlockRegion]>, level: usize)
+/// 
+/// The chain holds every definition of a block! region, from the most derived template to the base template. 
+/// level is the position in the chain of the definition currently being rendered.
+#[derive(Clone)]
+pub struct BlockFrame<'a>(pub Rc<[BlockRegion<'a>]>, pub usize);
 
-pub struct RuntimeContext {
-    output: String,
+pub struct RuntimeContext<'a> {
+    // Rendered chunks are forwarded to the sink as soon as they are produced
+    sink: &'a mut dyn fmt::Write,
     scope_ctx: ScopeContext,
     // Global context is data that is not found inside the template 
     // TODO Instead of using a DataContext, create a more user friendly API for global data. for now, this is fine
-    global_ctx: DataContext,
+    global_ctx: Option<&'a DataContext>,
+    // Templates available to include!, and the names of the templates currently being rendered 
+    env: Option<&'a Environment>,
+    include_chain: Vec<String>,
+    // block! regions overridden by templates that extend the template being rendered
+    block_overrides: HashMap<String, Vec<BlockRegion<'a>>>,
+    block_frames: Vec<BlockFrame<'a>>,
 }
 
-impl RuntimeContext {
-    pub fn new(global: Option<DataContext>) -> RuntimeContext {
+impl<'a> RuntimeContext<'a> {
+    pub fn new(global: Option<&'a DataContext>, sink: &'a mut dyn fmt::Write) -> RuntimeContext<'a> {
         RuntimeContext {
-            output: String::new(),
+            sink,
             scope_ctx: ScopeContext::new(),
-            global_ctx: global.unwrap_or(DataContext::new()),
+            global_ctx: global,
+            env: None,
+            include_chain: vec![],
+            block_overrides: HashMap::new(),
+            block_frames: vec![],
         }
     }
 
+    pub fn with_environment(mut self, env: &'a Environment) -> RuntimeContext<'a> {
+        self.env = Some(env);
+        self
+    }
+
+    pub fn environment(&self) -> Option<&'a Environment> {
+        self.env
+    }
+
+    pub fn include_chain(&self) -> &[String] {
+        &self.include_chain
+    }
+
+    pub fn push_include(&mut self, name: String) {
+        self.include_chain.push(name);
+    }
+
+    pub fn pop_include(&mut self) {
+        self.include_chain.pop();
+    }
+
+    /// Overrides must be added from the most derived template to the base template
+    pub fn add_block_override(&mut self, name: String, region: BlockRegion<'a>) {
+        self.block_overrides.entry(name).or_default().push(region);
+    }
+
+    pub fn block_overrides(&self, name: &str) -> Vec<BlockRegion<'a>> {
+        self.block_overrides.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Removes every override, so a template can be rendered without the overrides of the template hierarchy it was included from
+    pub fn take_block_overrides(&mut self) -> HashMap<String, Vec<BlockRegion<'a>>> {
+        std::mem::take(&mut self.block_overrides)
+    }
+
+    pub fn restore_block_overrides(&mut self, overrides: HashMap<String, Vec<BlockRegion<'a>>>) {
+        self.block_overrides = overrides;
+    }
+
+    pub fn push_block_frame(&mut self, frame: BlockFrame<'a>) {
+        self.block_frames.push(frame);
+    }
+
+    pub fn pop_block_frame(&mut self) {
+        self.block_frames.pop();
+    }
+
+    pub fn current_block_frame(&self) -> Option<BlockFrame<'a>> {
+        self.block_frames.last().cloned()
+    }
+
     pub fn open_scope(&mut self) {
         self.scope_ctx.open_scope();
     }
@@ -32,27 +119,23 @@ impl RuntimeContext {
         self.scope_ctx.insert(key, value)
     }
 
-    pub fn render(&mut self, item: &dyn Renderable) {
-        self.output.push_str(&item.render())
+    /// Writes the rendered item into the sink. The location is used to report a failed write.
+    pub fn render(&mut self, item: &dyn Renderable, location: Location) -> RuntimeResult<()> {
+        match self.sink.write_str(&item.render()) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(ExclaimError::Runtime(location, "Unable to write the rendered output to the sink.".to_string())),
+        }
     }
 
-    pub fn get(&self, key: &str) -> Data {
+    /// Borrows the data bound to the key, the flag is true when it is global data
+    pub fn get(&self, key: &str) -> Option<(&Data, bool)> {
         if let Some(data) = self.scope_ctx.get(key) {
-            data.clone()
+            Some((data, false))
         } else {
             // Check global context
             // Accessing key-values from global context may or may not exist, but we let the user deal with that since we can't make assumptions of the global data. 
             // Will be useful in future when data is pulled from data base
-            if let Some(data) = self.global_ctx.get(key) {
-                // Im curious if its faster to heap allocate here when necessary or keep Box<Data> for values in hashmap
-                Data::Option(Some(Box::new(data.clone())))
-            } else {
-                Data::Option(None)
-            }
+            self.global_ctx.and_then(|global| global.get(key)).map(|data| (data, true))
         }
     }
-
-    pub fn output(self) -> String {
-        self.output
-    }
 }
\ No newline at end of file
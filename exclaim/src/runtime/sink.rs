@@ -0,0 +1,63 @@
+use std::fmt;
+use std::io;
+
+use crate::common::{
+    ExclaimError,
+    Location,
+};
+
+/// Adapts an io::Write into a fmt::Write sink for the runtime. 
+/// 
+/// fmt::Error can't carry any details, so the io::Error is kept around for the caller to report.
+pub struct IoSink<'a, W: io::Write> {
+    writer: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<'a, W: io::Write> IoSink<'a, W> {
+    pub fn new(writer: &'a mut W) -> IoSink<'a, W> {
+        IoSink {
+            writer,
+            error: None,
+        }
+    }
+
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Turns the result of a run into this sink into the caller's result and flushes the writer. 
+    /// A runtime error caused by a failed write is reported with the io::Error instead of the fmt::Error.
+    pub fn finish(mut self, result: Result<(), ExclaimError>) -> Result<(), ExclaimError> {
+        match result {
+            Ok(()) => (),
+            Err(ExclaimError::Runtime(location, msg)) => {
+                let msg = match self.take_error() {
+                    Some(e) => format!("Unable to write the rendered output: {}", e),
+                    None => msg,
+                };
+                return Err(ExclaimError::Runtime(location, msg));
+            },
+            Err(e) => return Err(e),
+        }
+
+        self.flush()
+            .map_err(|e| ExclaimError::Runtime(Location::default(), format!("Unable to flush the rendered output: {}", e)))
+    }
+}
+
+impl<'a, W: io::Write> fmt::Write for IoSink<'a, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self.writer.write_all(s.as_bytes()) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                self.error = Some(e);
+                Err(fmt::Error)
+            }
+        }
+    }
+}
@@ -1,18 +1,43 @@
 use crate::ast::prelude::*;
+use crate::common::{
+    ExclaimError,
+    Location,
+};
+use crate::tokens::Action;
 
 pub mod scope;
 use scope::Scope;
 
-type SemanticResult<T> = Result<T, String>;
+type SemanticResult<T> = Result<T, ExclaimError>;
+
+/// Kinds of unclosed blocks, elif! and else! can only continue a Branch
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Unclosed {
+    // if! or elif!
+    Branch,
+    Else,
+    Other,
+}
 
 struct SemanticContext {
     scope: Scope,
+    // Names of the block! regions declared in the template 
+    block_names: Vec<String>,
+    // Number of block! regions the current block is nested in
+    block_depth: usize,
+    // Unclosed blocks the current block is nested in
+    unclosed: Vec<Unclosed>,
+    has_extends: bool,
 }
 
 impl SemanticContext {
     pub fn new() -> SemanticContext {
         SemanticContext {
-            scope: Scope::new()
+            scope: Scope::new(),
+            block_names: vec![],
+            block_depth: 0,
+            unclosed: vec![],
+            has_extends: false,
         }
     }
 
@@ -26,7 +51,7 @@ macro_rules! unwrap_index {
         if let Some(value) = $index {
             value
         } else {
-            return Err("Expected an AST Index.".to_string());
+            return Err(ExclaimError::Semantic(Location::default(), "Expected an AST Index.".to_string()));
         }
     };
 }
@@ -48,31 +73,58 @@ fn analyze(mut ast: Ast) -> SemanticResult<Ast> {
 
 fn analyze_block(ast: &mut Ast, ctx: &mut SemanticContext, block: Option<AstIndex>) -> SemanticResult<Option<AstIndex>> {
     let block = unwrap_index!(block);
-    let block_cell = ast.get(block);
-    let mut block_ref = block_cell.borrow_mut();
     
     // Check element is a block
-    match &mut *block_ref {
-        AstElement::Block(_, block) => { 
+    match ast.get(block) {
+        AstElement::Block(index, block) => { 
             match block {
                 // Text Blocks can't fail in this context, because they are just text
                 Block::Text(_, next) => Ok(*next),
-                Block::CodeEnclosed(_, next) => {
-                    // TODO analyze the statement - Not necessary for now
+                Block::CodeEnclosed(statement, next) => {
+                    analyze_statement(ast, ctx, *statement)?;
                     Ok(*next) 
                 }
-                Block::CodeUnclosed(_, block_scope, next_block) => { 
-                    // TODO analyze the statement - Not necessary for now
+                Block::CodeUnclosed(statement, _, next_block) => { 
+                    let (index, statement, next_block) = (*index, *statement, *next_block);
+                    let is_region = analyze_statement(ast, ctx, statement)?;
+                    if is_region {
+                        ctx.block_depth += 1;
+                    }
+
+                    // elif! and else! continue the branch they are nested in
+                    let conditional = conditional_action(ast, statement);
+                    if let Some(action @ Action::Elif) | Some(action @ Action::Else) = conditional {
+                        match ctx.unclosed.last() {
+                            Some(Unclosed::Branch) => (),
+                            Some(Unclosed::Else) => return Err(ExclaimError::Semantic(
+                                statement_location(ast, statement), 
+                                format!("{} can't follow an else! branch.", action.as_str()),
+                            )),
+                            _ => return Err(ExclaimError::Semantic(
+                                statement_location(ast, statement), 
+                                format!("{} can only follow an if! or elif! branch.", action.as_str()),
+                            )),
+                        }
+                    }
+                    ctx.unclosed.push(match conditional {
+                        Some(Action::If) | Some(Action::Elif) => Unclosed::Branch,
+                        Some(_) => Unclosed::Else,
+                        None => Unclosed::Other,
+                    });
                     
                     // Open Scope 
                     ctx.scope().open();
 
                     // Build the scope until it is closed
-                    let mut current_scoped_block = *next_block;
+                    let mut block_scope: Vec<AstIndex> = vec![];
+                    let mut current_scoped_block = next_block;
                     while !ctx.scope().was_closed() {
-                        let next_scoped_block = match analyze_block(ast, ctx, current_scoped_block) {
-                            Ok(index) => index,
-                            Err(_) => return Err("Expected the scope to be closed with {{!}}".to_string()),
+                        let next_scoped_block = match current_scoped_block {
+                            Some(_) => analyze_block(ast, ctx, current_scoped_block)?,
+                            None => return Err(ExclaimError::Semantic(
+                                statement_location(ast, statement), 
+                                "Expected the scope to be closed with {{!}}".to_string()
+                            )),
                         };
 
                         // analyze_block() would return an error if current_idx is None
@@ -80,14 +132,38 @@ fn analyze_block(ast: &mut Ast, ctx: &mut SemanticContext, block: Option<AstInde
                         block_scope.push(current_scoped_block.unwrap());
                         current_scoped_block = next_scoped_block;
                     }
-                    *next_block = current_scoped_block;
 
-                    Ok(*next_block)
+                    // The unclosed block spans up to the end of its closing block
+                    if let Some(closing_block) = block_scope.last() {
+                        let span = ast.span(index).to(ast.span(*closing_block));
+                        ast.set_span(index, span);
+                    }
+
+                    // The scope of the block is known now, so the block continues after its closing block
+                    if let AstElement::Block(_, Block::CodeUnclosed(_, scope, next)) = ast.get_mut(index) {
+                        *scope = block_scope;
+                        *next = current_scoped_block;
+                    }
+
+                    if is_region {
+                        ctx.block_depth -= 1;
+                    }
+
+                    // The branch before elif! or else! ends with it, so its scope is closed as well
+                    ctx.unclosed.pop();
+                    if let Some(Action::Elif) | Some(Action::Else) = conditional {
+                        ctx.scope().close();
+                    }
+
+                    Ok(current_scoped_block)
                 }
-                Block::CodeClosing(_, next) => {
+                Block::CodeClosing(statement, next) => {
                     if ctx.scope().level() == scope::FILE_SCOPE {
                         // A closing block should not exist by itself in the file scope
-                        return Err("Invalid lone closing block in file scope. Closing blocks must only be used to close an opened scope.".to_string());
+                        Err(ExclaimError::Semantic(
+                            statement_location(ast, *statement), 
+                            "Invalid lone closing block in file scope. Closing blocks must only be used to close an opened scope.".to_string()
+                        ))
                     } else {
                         ctx.scope().close();
                         Ok(*next)
@@ -95,6 +171,63 @@ fn analyze_block(ast: &mut Ast, ctx: &mut SemanticContext, block: Option<AstInde
                 }
             }
         }
-        _ => return Err("Expected a Block!".to_string()),
+        _ => Err(ExclaimError::Semantic(Location::default(), "Expected a Block!".to_string())),
+    }
+}
+
+/// Checks the statement is valid where it is used. Returns true if the statement opens a block! region.
+fn analyze_statement(ast: &Ast, ctx: &mut SemanticContext, statement: AstIndex) -> SemanticResult<bool> {
+    match ast.get(statement) {
+        AstElement::Statement(_, statement) => {
+            match statement {
+                Statement::Extends(action, _) => {
+                    if ctx.scope().level() != scope::FILE_SCOPE {
+                        return Err(ExclaimError::Semantic(action.location(), "extends! can only be used in the file scope.".to_string()));
+                    }
+                    if ctx.has_extends {
+                        return Err(ExclaimError::Semantic(action.location(), "A template can only extend one other template.".to_string()));
+                    }
+
+                    ctx.has_extends = true;
+                    Ok(false)
+                },
+                Statement::Block(_, name) => {
+                    let label = name.label().unwrap_or_default().to_string();
+                    if ctx.block_names.contains(&label) {
+                        return Err(ExclaimError::Semantic(name.location(), format!("The block! region '{}' is declared more than once.", label)));
+                    }
+
+                    ctx.block_names.push(label);
+                    Ok(true)
+                },
+                Statement::Super(action) => {
+                    if ctx.block_depth == 0 {
+                        return Err(ExclaimError::Semantic(action.location(), "super! can only be used inside of a block! region.".to_string()));
+                    }
+
+                    Ok(false)
+                },
+                _ => Ok(false),
+            }
+        },
+        _ => Err(ExclaimError::Semantic(Location::default(), "Expected a statement.".to_string())),
+    }
+}
+
+/// The action of if!, elif! and else! statements
+fn conditional_action(ast: &Ast, statement: AstIndex) -> Option<Action> {
+    match ast.get(statement) {
+        AstElement::Statement(_, Statement::If(_, _)) => Some(Action::If),
+        AstElement::Statement(_, Statement::Elif(_, _)) => Some(Action::Elif),
+        AstElement::Statement(_, Statement::Else(_)) => Some(Action::Else),
+        _ => None,
+    }
+}
+
+/// Location of the action that starts the statement 
+fn statement_location(ast: &Ast, statement: AstIndex) -> Location {
+    match ast.get(statement) {
+        AstElement::Statement(_, statement) => statement.action().location(),
+        _ => Location::default(),
     }
 }
\ No newline at end of file
@@ -20,12 +20,10 @@ impl Scope {
     }
 
     pub fn open(&mut self) {
-        println!("Opened scope!");
         self.level += 1;
     }
 
     pub fn close(&mut self) {
-        println!("Closed scope!");
         self.level -= 1;
         self.was_closed = true;
     }
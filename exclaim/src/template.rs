@@ -0,0 +1,68 @@
+use std::fmt;
+use std::io;
+
+use crate::ast::prelude::*;
+use crate::common::ExclaimError;
+use crate::data::DataContext;
+use crate::options::CompileOptions;
+
+use crate::lexer;
+use crate::parser;
+use crate::semantics;
+use crate::runtime;
+
+/// A compiled template. 
+/// 
+/// The source is lexed, parsed and analyzed once by compile(), so the template can be rendered any number of times.
+pub struct Template {
+    // Borrows its text from source, see compile_with_options(). Declared first, so it is dropped before the source
+    ast: Ast<'static>,
+    source: String,
+}
+
+impl Template {
+    pub fn compile(input: &str) -> Result<Template, ExclaimError> {
+        Template::compile_with_options(input, &CompileOptions::default())
+    }
+
+    pub fn compile_with_options(input: &str, options: &CompileOptions) -> Result<Template, ExclaimError> {
+        // The template keeps one copy of the input and the tokens borrow their text from it
+        let source = input.to_string();
+
+        // SAFETY: The text lives in the heap buffer of source, which doesn't move when the template is moved and is never 
+        // modified or dropped while the ast exists. The ast is only lent out with the lifetime of the template, see ast().
+        let borrowed: &'static str = unsafe { &*(source.as_str() as *const str) };
+
+        let tokens = lexer::run_with_options(borrowed, options)?;
+        let ast = parser::run_with_source(tokens, borrowed)?;
+        let ast = semantics::run(ast)?;
+
+        Ok(Template { ast, source })
+    }
+
+    /// The source the template was compiled from
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn render(&self, data: &DataContext) -> Result<String, ExclaimError> {
+        runtime::run(&self.ast, Some(data))
+    }
+
+    /// Renders the template into a fmt::Write sink chunk by chunk, instead of building the whole output first.
+    pub fn render_to<W: fmt::Write>(&self, data: &DataContext, sink: &mut W) -> Result<(), ExclaimError> {
+        runtime::run_to(&self.ast, Some(data), sink)
+    }
+
+    /// Renders the template into an io::Write sink (file, socket, BufWriter, ...). 
+    /// I/O errors are reported as runtime errors at the location of the chunk that failed to write.
+    pub fn write_to<W: io::Write>(&self, data: &DataContext, writer: &mut W) -> Result<(), ExclaimError> {
+        let mut sink = runtime::IoSink::new(writer);
+        let result = runtime::run_to(&self.ast, Some(data), &mut sink);
+        sink.finish(result)
+    }
+
+    pub fn ast(&self) -> &Ast<'_> {
+        &self.ast
+    }
+}
@@ -1,19 +1,26 @@
+use std::borrow::Cow;
 use std::fmt::Display;
 
 use crate::ast::AstIndex;
-use crate::common::Location;
+use crate::common::{
+    Location,
+    Span,
+};
 use crate::common::serialize::*;
 
 use crate::data::traits::Renderable;
 
+/// Text, string literals and labels borrow from the source, unless they had to be copied (escape sequences, skipped characters).
 #[derive(Debug, PartialEq, Clone)]
-pub enum Token {
-    StringLiteral(String, Location),
-    NumberLiteral(Number, Location),
+pub enum Token<'a> {
+    StringLiteral(Cow<'a, str>, Span),
+    NumberLiteral(Number, Span),
+    BoolLiteral(bool, Span),
+    NullLiteral(Span),
 
-    Label(String, Location),
-    Operator(Op, Location),
-    Action(Action, Location),
+    Label(Cow<'a, str>, Span),
+    Operator(Op, Span),
+    Action(Action, Span),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -25,35 +32,65 @@ pub enum Number {
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Action {
+    Block,
+    Elif,
+    Else,
     End,
+    Extends,
+    If,
+    Include,
     Let, 
     Render,
+    Super,
     Write,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Op {
+    Add,            // +
     And,            // &&
     Assign,         // =
     BlockClose,     // }}
-    BlockClosePrime,// } Reserved
+    BlockClosePrime,// }
     BlockOpen,      // {{
-    BlockOpenPrime, // { Reserved
-    ClosureOpen,    // [ Reserved
-    ClosureClose,   // ] Reserved
+    BlockOpenPrime, // {
+    ClosureOpen,    // [
+    ClosureClose,   // ]
     Comma,          // , 
+    Divide,         // /
     Dot,            // . 
     Each,           // :
     Equality,       // ==
+    Greater,        // >
+    GreaterEqual,   // >=
     Inequality,     // !=
+    Less,           // <
+    LessEqual,      // <=
+    Multiply,       // *
+    Not,            // !
     Or,             // || 
     ParenOpen,      // (
     ParenClose,     // )
     Pipe,           // | (Chain function operations)
+    Remainder,      // %
+    Subtract,       // -
 }
 
-impl Token {
-    pub fn string_literal(&self) -> Option<&String> {
+impl<'a> Token<'a> {
+    /// Copies the borrowed text, so the token no longer depends on the source
+    pub fn into_owned(self) -> Token<'static> {
+        match self {
+            Token::StringLiteral(literal, span) => Token::StringLiteral(Cow::Owned(literal.into_owned()), span),
+            Token::NumberLiteral(literal, span) => Token::NumberLiteral(literal, span),
+            Token::BoolLiteral(literal, span) => Token::BoolLiteral(literal, span),
+            Token::NullLiteral(span) => Token::NullLiteral(span),
+            Token::Label(label, span) => Token::Label(Cow::Owned(label.into_owned()), span),
+            Token::Operator(op, span) => Token::Operator(op, span),
+            Token::Action(action, span) => Token::Action(action, span),
+        }
+    }
+
+    pub fn string_literal(&self) -> Option<&str> {
         match self {
             Token::StringLiteral(literal, _) => Some(literal),
             _ => None
@@ -67,6 +104,13 @@ impl Token {
         }
     }
 
+    pub fn bool_literal(&self) -> Option<bool> {
+        match self {
+            Token::BoolLiteral(literal, _) => Some(*literal),
+            _ => None
+        }
+    }
+
     pub fn label(&self) -> Option<&str> {
         match self {
             Token::Label(label, _) => Some(label),
@@ -87,45 +131,126 @@ impl Token {
             _ => None
         }
     }
+
+    pub fn location(&self) -> Location {
+        self.span().start()
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Token::StringLiteral(_, span) => *span,
+            Token::NumberLiteral(_, span) => *span,
+            Token::BoolLiteral(_, span) => *span,
+            Token::NullLiteral(span) => *span,
+            Token::Label(_, span) => *span,
+            Token::Operator(_, span) => *span,
+            Token::Action(_, span) => *span,
+        }
+    }
+}
+
+impl Action {
+    /// The action as it is written in a template
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Block => "block!",
+            Action::Elif => "elif!",
+            Action::Else => "else!",
+            Action::End => "!",
+            Action::Extends => "extends!",
+            Action::If => "if!",
+            Action::Include => "include!",
+            Action::Let => "let!",
+            Action::Render => "render!",
+            Action::Super => "super!",
+            Action::Write => "write!",
+        }
+    }
 }
 
-impl Serializable for Token {
+impl Op {
+    /// The operator as it is written in a template
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Op::Add => "+",
+            Op::And => "&&",
+            Op::Assign => "=",
+            Op::BlockClose => "}}",
+            Op::BlockClosePrime => "}",
+            Op::BlockOpen => "{{",
+            Op::BlockOpenPrime => "{",
+            Op::ClosureOpen => "[",
+            Op::ClosureClose => "]",
+            Op::Comma => ",",
+            Op::Divide => "/",
+            Op::Dot => ".",
+            Op::Each => ":",
+            Op::Equality => "==",
+            Op::Greater => ">",
+            Op::GreaterEqual => ">=",
+            Op::Inequality => "!=",
+            Op::Less => "<",
+            Op::LessEqual => "<=",
+            Op::Multiply => "*",
+            Op::Not => "!",
+            Op::Or => "||",
+            Op::ParenOpen => "(",
+            Op::ParenClose => ")",
+            Op::Pipe => "|",
+            Op::Remainder => "%",
+            Op::Subtract => "-",
+        }
+    }
+}
+
+impl Serializable for Token<'_> {
     fn serialize(&self, serde: &mut Serializer, ctx: &dyn IndexSerializable) -> Option<AstIndex> {
         match self {
-            Token::StringLiteral(literal, loc) => {
+            Token::StringLiteral(literal, span) => {
                 let _token = serde.open_tag("StringLiteral");
                 serde.terminal("value", &format!("{:?}", literal));
-                loc.serialize(serde, ctx)
+                span.start().serialize(serde, ctx)
             }
-            Token::NumberLiteral(literal, loc) => {
+            Token::NumberLiteral(literal, span) => {
                 let _token = serde.open_tag("NumberLiteral");
                 serde.terminal("value", &literal.to_string());
-                loc.serialize(serde, ctx)
+                span.start().serialize(serde, ctx)
             }
-            Token::Label(label, loc) => {
+            Token::BoolLiteral(literal, span) => {
+                let _token = serde.open_tag("BoolLiteral");
+                serde.terminal("value", &literal.to_string());
+                span.start().serialize(serde, ctx)
+            }
+            Token::NullLiteral(span) => {
+                let _token = serde.open_tag("NullLiteral");
+                span.start().serialize(serde, ctx)
+            }
+            Token::Label(label, span) => {
                 let _token = serde.open_tag("Label");
                 serde.terminal("value", &format!("{:?}", label));
-                loc.serialize(serde, ctx)
+                span.start().serialize(serde, ctx)
             }
-            Token::Operator(op, loc) => {
+            Token::Operator(op, span) => {
                 let _token = serde.open_tag("Operator");
                 serde.terminal("value", &format!("{:?}", op));
-                loc.serialize(serde, ctx)
+                span.start().serialize(serde, ctx)
             }
-            Token::Action(action, loc) => {
+            Token::Action(action, span) => {
                 let _token = serde.open_tag("Action");
                 serde.terminal("value", &format!("{:?}", action));
-                loc.serialize(serde, ctx)
+                span.start().serialize(serde, ctx)
             }
         }
     }
 }
 
-impl Renderable for Token {
+impl Renderable for Token<'_> {
     fn render(&self) -> String {
         match self {
             Token::StringLiteral(literal, _) => literal.to_string(),
             Token::NumberLiteral(literal, _) => literal.to_string(),
+            Token::BoolLiteral(literal, _) => literal.to_string(),
+            Token::NullLiteral(_) => String::from("null"),
             Token::Label(label, _) => label.to_string(),
             Token::Operator(op, _) => format!("{:?}", op),
             Token::Action(action, _) => format!("{:?}", action),
@@ -133,6 +258,21 @@ impl Renderable for Token {
     }
 }
 
+/// Displays the token as it is written in a template
+impl Display for Token<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::StringLiteral(literal, _) => write!(f, "{:?}", literal),
+            Token::NumberLiteral(literal, _) => write!(f, "{}", literal),
+            Token::BoolLiteral(literal, _) => write!(f, "{}", literal),
+            Token::NullLiteral(_) => write!(f, "null"),
+            Token::Label(label, _) => write!(f, "{}", label),
+            Token::Operator(op, _) => write!(f, "{}", op.as_str()),
+            Token::Action(action, _) => write!(f, "{}", action.as_str()),
+        }
+    }
+}
+
 impl Display for Number {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -31,6 +31,11 @@ impl<'a> fmt::Debug for PrettyString<'a> {
 #[macro_export]
 macro_rules! assert_eq {
     ($left:expr, $right:expr) => {
-        pretty_assertions::assert_eq!(PrettyString($left), PrettyString($right));
+        pretty_assertions::assert_eq!(PrettyString($left), PrettyString($right))
     }
-}
\ No newline at end of file
+}
+
+/// Pretty prints values that aren't strings, so they can be compared with the PrettyString version of assert_eq!
+pub fn debug_string<T: fmt::Debug>(value: &T) -> String {
+    format!("{:#?}", value)
+}
@@ -0,0 +1,190 @@
+use exclaim::{
+    Data,
+    DataContext,
+    Environment,
+    Template,
+};
+
+#[test]
+fn environment_render() {
+    let mut env = Environment::new();
+    env.add_template("page", r#"Hello, {{ write! name | unwrap }}!"#).unwrap();
+
+    let mut data = DataContext::new();
+    data.insert("name".to_string(), Data::String("Earth".to_string()));
+
+    let output = env.render("page", &data).unwrap();
+    pretty_assertions::assert_eq!(output, "Hello, Earth!");
+}
+
+#[test]
+fn environment_include() {
+    let mut env = Environment::new();
+    env.add_template("header.html", r#"<h1>{{ write! title }}</h1>"#).unwrap();
+    env.add_template("page", r#"{{ let! title = "Exclaim" }}{{ include! "header.html" }}<p>body</p>"#).unwrap();
+
+    let output = env.render("page", &DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "<h1>Exclaim</h1><p>body</p>");
+}
+
+#[test]
+fn environment_include_in_render_block() {
+    let mut env = Environment::new();
+    env.add_template("item", r#"<li>{{ write! ch }}</li>"#).unwrap();
+    env.add_template("list", r#"<ul>{{ render! ch : "ABC" | chars }}{{ include! "item" }}{{!}}</ul>"#).unwrap();
+
+    let output = env.render("list", &DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "<ul><li>A</li><li>B</li><li>C</li></ul>");
+}
+
+#[test]
+fn environment_write_to_io_sink() {
+    let mut env = Environment::new();
+    env.add_template("item", r#"<li>{{ write! ch }}</li>"#).unwrap();
+    env.add_template("list", r#"<ul>{{ render! ch : "AB" | chars }}{{ include! "item" }}{{!}}</ul>"#).unwrap();
+
+    let mut output: Vec<u8> = vec![];
+    env.write_to("list", &DataContext::new(), &mut output).unwrap();
+    pretty_assertions::assert_eq!(String::from_utf8(output).unwrap(), "<ul><li>A</li><li>B</li></ul>");
+}
+
+#[test]
+fn environment_include_scope_does_not_leak() {
+    let mut env = Environment::new();
+    env.add_template("inner", r#"{{ let! name = "inner" }}{{ write! name }} "#).unwrap();
+    env.add_template("outer", r#"{{ let! name = "outer" }}{{ include! "inner" }}{{ write! name }}"#).unwrap();
+
+    let output = env.render("outer", &DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "inner outer");
+}
+
+#[test]
+fn environment_include_cycle() {
+    let mut env = Environment::new();
+    env.add_template("a", r#"A{{ include! "b" }}"#).unwrap();
+    env.add_template("b", r#"B{{ include! "a" }}"#).unwrap();
+
+    let error = env.render("a", &DataContext::new()).unwrap_err();
+    pretty_assertions::assert_eq!(error.stage(), "Runtime");
+    pretty_assertions::assert_eq!(error.message(), "Include cycle detected while including 'a'. (include chain: a -> b -> a)");
+}
+
+#[test]
+fn environment_include_missing_template() {
+    let mut env = Environment::new();
+    env.add_template("page", r#"{{ include! "missing" }}"#).unwrap();
+
+    let error = env.render("page", &DataContext::new()).unwrap_err();
+    pretty_assertions::assert_eq!(error.message(), "Unable to include 'missing', the template is not registered in the environment.");
+}
+
+#[test]
+fn environment_include_error_reports_chain() {
+    let mut env = Environment::new();
+    env.add_template("footer", r#"{{ write! -1 | uint }}"#).unwrap();
+    env.add_template("page", r#"{{ include! "footer" }}"#).unwrap();
+
+    let error = env.render("page", &DataContext::new()).unwrap_err();
+    pretty_assertions::assert_eq!(error.stage(), "Transform");
+    pretty_assertions::assert_eq!(error.message(), "Unable to transform a negative integer into an unsigned integer (include chain: page -> footer)");
+}
+
+#[test]
+fn environment_add_template_error() {
+    let mut env = Environment::new();
+
+    let error = env.add_template("broken", r#"{{ write! }}"#).unwrap_err();
+    assert!(error.message().starts_with("In template 'broken': "));
+}
+
+#[test]
+fn template_include_without_environment() {
+    let template = Template::compile(r#"{{ include! "header" }}"#).unwrap();
+
+    let error = template.render(&DataContext::new()).unwrap_err();
+    pretty_assertions::assert_eq!(error.stage(), "Runtime");
+}
+
+#[test]
+fn environment_extends_overrides_block() {
+    let mut env = Environment::new();
+    env.add_template("base", r#"<title>{{ block! title }}Default{{!}}</title><main>{{ block! content }}{{!}}</main>"#).unwrap();
+    env.add_template("page", r#"{{ extends! "base" }}ignored{{ block! content }}Hello{{!}}"#).unwrap();
+
+    let output = env.render("page", &DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "<title>Default</title><main>Hello</main>");
+}
+
+#[test]
+fn environment_extends_super() {
+    let mut env = Environment::new();
+    env.add_template("base", r#"{{ block! title }}Exclaim{{!}}"#).unwrap();
+    env.add_template("page", r#"{{ extends! "base" }}{{ block! title }}Page - {{ super! }}{{!}}"#).unwrap();
+
+    let output = env.render("page", &DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "Page - Exclaim");
+}
+
+#[test]
+fn environment_extends_multiple_levels() {
+    let mut env = Environment::new();
+    env.add_template("base", r#"[{{ block! content }}base{{!}}]"#).unwrap();
+    env.add_template("layout", r#"{{ extends! "base" }}{{ block! content }}layout {{ super! }}{{ block! inner }}{{!}}{{!}}"#).unwrap();
+    env.add_template("page", r#"{{ extends! "layout" }}{{ let! name = "page" }}{{ block! inner }} {{ write! name }} {{ super! }}{{!}}{{ block! content }}{{ super! }}!{{!}}"#).unwrap();
+
+    let output = env.render("page", &DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "[layout base page !]");
+}
+
+#[test]
+fn environment_extends_base_renders_alone() {
+    let mut env = Environment::new();
+    env.add_template("base", r#"<main>{{ block! content }}Default{{!}}</main>"#).unwrap();
+
+    let output = env.render("base", &DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "<main>Default</main>");
+}
+
+#[test]
+fn environment_extends_super_without_parent() {
+    let mut env = Environment::new();
+    env.add_template("base", r#"{{ block! content }}{{ super! }}{{!}}"#).unwrap();
+
+    let error = env.render("base", &DataContext::new()).unwrap_err();
+    pretty_assertions::assert_eq!(error.message(), "super! was used, but the block! region does not override a parent region.");
+}
+
+#[test]
+fn environment_extends_cycle() {
+    let mut env = Environment::new();
+    env.add_template("a", r#"{{ extends! "b" }}"#).unwrap();
+    env.add_template("b", r#"{{ extends! "a" }}"#).unwrap();
+
+    let error = env.render("a", &DataContext::new()).unwrap_err();
+    pretty_assertions::assert_eq!(error.message(), "Extends cycle detected while extending 'a'. (include chain: a -> b -> a)");
+}
+
+#[test]
+fn environment_include_ignores_overrides() {
+    let mut env = Environment::new();
+    env.add_template("footer", r#"{{ block! content }}footer{{!}}"#).unwrap();
+    env.add_template("base", r#"{{ block! content }}base{{!}} {{ include! "footer" }}"#).unwrap();
+    env.add_template("page", r#"{{ extends! "base" }}{{ block! content }}page{{!}}"#).unwrap();
+
+    let output = env.render("page", &DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "page footer");
+}
+
+#[test]
+fn template_extends_without_environment() {
+    let template = Template::compile(r#"{{ extends! "base" }}"#).unwrap();
+
+    let error = template.render(&DataContext::new()).unwrap_err();
+    pretty_assertions::assert_eq!(error.message(), "Unable to extend 'base', extends! is only available when rendering through an Environment.");
+}
+
+#[test]
+fn environment_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Environment>();
+}
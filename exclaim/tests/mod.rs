@@ -2,3 +2,5 @@ mod common;
 mod parser;
 mod semantics;
 mod runtime;
+mod template;
+mod environment;
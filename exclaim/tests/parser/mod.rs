@@ -1,7 +1,13 @@
 use exclaim::common::serialize::*;
+use exclaim::common::{
+    Location,
+    Span,
+};
+use exclaim::ExclaimError;
 use crate::common::{
     PrettyString,
     read_file_to_string,
+    debug_string,
 };
 
 // Overrides std lib assert_eq with PrettyString version of assert_eq. 
@@ -158,6 +164,72 @@ fn parse_stmt_render_iterable() {
     assert_eq!(&Serializer::serialize(&ast), &expected);
 }
 
+#[test]
+fn parse_stmt_conditionals() {
+    let input = "{{ if! ready }}{{ elif! true }}{{ else! }}";
+    let expected = read_file_to_string("./tests/parser/output/stmt_conditionals.ast");
+
+    let tokens = exclaim::run_lexer(input);
+    let ast = exclaim::run_parser(tokens);
+
+    assert_eq!(&Serializer::serialize(&ast), &expected);
+}
+
+#[test]
+fn parse_expr_precedence() {
+    let input = "{{ write! !a || b && c == 1 < 2 }}";
+    let expected = read_file_to_string("./tests/parser/output/expr_precedence.ast");
+
+    let tokens = exclaim::run_lexer(input);
+    let ast = exclaim::run_parser(tokens);
+
+    assert_eq!(&Serializer::serialize(&ast), &expected);
+}
+
+#[test]
+fn parse_expr_arithmetic() {
+    let input = "{{ write! (a + 1) * 2 | string - b % 3 }}";
+    let expected = read_file_to_string("./tests/parser/output/expr_arithmetic.ast");
+
+    let tokens = exclaim::run_lexer(input);
+    let ast = exclaim::run_parser(tokens);
+
+    assert_eq!(&Serializer::serialize(&ast), &expected);
+}
+
+#[test]
+fn parse_expr_negation() {
+    let input = "{{ write! -a * -(b + 1) - -2 | string }}";
+    let expected = read_file_to_string("./tests/parser/output/expr_negation.ast");
+
+    let tokens = exclaim::run_lexer(input);
+    let ast = exclaim::run_parser(tokens);
+
+    assert_eq!(&Serializer::serialize(&ast), &expected);
+}
+
+#[test]
+fn parse_expr_collections() {
+    let input = "{{ let! page = { title: \"x\", \"tags\": [1, 2], pair: (a, b) | len } }}";
+    let expected = read_file_to_string("./tests/parser/output/expr_collections.ast");
+
+    let tokens = exclaim::run_lexer(input);
+    let ast = exclaim::run_parser(tokens);
+
+    assert_eq!(&Serializer::serialize(&ast), &expected);
+}
+
+#[test]
+fn parse_expr_access() {
+    let input = "{{ write! items[i + 1].name | len }}{{ write! [a][0] }}";
+    let expected = read_file_to_string("./tests/parser/output/expr_access.ast");
+
+    let tokens = exclaim::run_lexer(input);
+    let ast = exclaim::run_parser(tokens);
+
+    assert_eq!(&Serializer::serialize(&ast), &expected);
+}
+
 #[test]
 fn parse_sample() {
     let input = read_file_to_string("./tests/parser/input/sample.txt");
@@ -167,4 +239,195 @@ fn parse_sample() {
     let ast = exclaim::run_parser(tokens);
 
     assert_eq!(&Serializer::serialize(&ast), &expected);
-}
\ No newline at end of file
+}
+#[test]
+fn parse_try_run_parser_error() {
+    let input = "{{ write! variable. }}";
+
+    let tokens = exclaim::try_run_lexer(input).unwrap();
+    let error = exclaim::try_run_parser(tokens).err().expect("Expected an error.");
+
+    match error {
+        ExclaimError::Parse(location, msg) => {
+            assert_eq!(&debug_string(&location), &debug_string(&Location::with_offset(0, 20, 20)));
+            assert_eq!(&msg, "Expected a label after the dot operator, but found '}}'.");
+        },
+        _ => panic!("Expected a parse error, but got: {}", error),
+    }
+}
+
+#[test]
+fn parse_try_run_error_snippet() {
+    let input = "<ul>\n{{ render! item items }}{{!}}";
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+
+    match error {
+        ExclaimError::Parse(location, msg) => {
+            assert_eq!(&debug_string(&location), &debug_string(&Location::with_offset(1, 16, 21)));
+            assert_eq!(&msg, concat!(
+                "Expected ':' to iterate over an expression in the render! statement, but found 'items'. On line [1; 16]:\n",
+                "\t{{ render! item items }}{{!}}\n",
+                "\t                ^ expected ':' to iterate over an expression in the render! statement",
+            ));
+        },
+        _ => panic!("Expected a parse error, but got: {}", error),
+    }
+}
+
+#[test]
+fn parse_try_run_error_end_of_token_stream() {
+    let input = "{{ write! name";
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+
+    assert_eq!(&debug_string(&error.location()), &debug_string(&Location::with_offset(0, 10, 10)));
+    assert!(error.message().starts_with("Expected '}}' to close the code block, but reached the end of the template."));
+}
+
+#[test]
+fn parse_try_run_parser_multiple_errors() {
+    let input = "{{ write! a. }} text {{ let! x 5 }}\n{{ write! \"valid\" }}{{ render! a b }}{{!}}";
+
+    let tokens = exclaim::try_run_lexer(input).unwrap();
+    let error = exclaim::try_run_parser(tokens).err().expect("Expected an error.");
+
+    let errors: Vec<(Location, &str)> = error.errors()
+        .iter()
+        .map(|e| (e.location(), e.message()))
+        .collect();
+
+    assert_eq!(&debug_string(&errors), &debug_string(&vec![
+        (Location::with_offset(0, 13, 13), "Expected a label after the dot operator, but found '}}'."),
+        (Location::with_offset(0, 31, 31), "Expected '=' to assign an expression in the let! statement, but found '5'."),
+        (Location::with_offset(1, 33, 69), "Expected ':' to iterate over an expression in the render! statement, but found 'b'."),
+    ]));
+
+    // The first error is used as the location of the whole error
+    assert_eq!(&debug_string(&error.location()), &debug_string(&Location::with_offset(0, 13, 13)));
+    assert_eq!(error.stage(), "Parser");
+}
+
+#[test]
+fn parse_try_run_parser_single_error_is_not_multiple() {
+    let input = "{{ write! a. }}{{ write! b }}";
+
+    let tokens = exclaim::try_run_lexer(input).unwrap();
+    match exclaim::try_run_parser(tokens) {
+        Ok(_) => panic!("Expected an error."),
+        Err(ExclaimError::Parse(location, _)) => assert_eq!(&debug_string(&location), &debug_string(&Location::with_offset(0, 13, 13))),
+        Err(e) => panic!("Expected a single parse error, but got: {}", e),
+    }
+}
+
+#[test]
+fn parse_block_spans() {
+    let input = "{{ render! item : items }}\n{{ write! item | uppercase }}\n{{!}} after";
+
+    let tokens = exclaim::run_lexer(input);
+    let ast = exclaim::run_semantics(exclaim::run_parser(tokens));
+
+    // The render! block spans up to the end of its closing block
+    let head = ast.head().unwrap();
+    assert_eq!(&debug_string(&ast.span(head)), &debug_string(&Span::new(Location::with_offset(0, 0, 0), Location::with_offset(2, 5, 62))));
+    assert_eq!(&input[ast.span(head).range()], "{{ render! item : items }}\n{{ write! item | uppercase }}\n{{!}}");
+}
+
+#[test]
+fn parse_try_run_negative_literal_out_of_range() {
+    let input = "{{ write! -9223372036854775809 }}";
+
+    let tokens = exclaim::try_run_lexer(input).unwrap();
+    let error = exclaim::try_run_parser(tokens).err().expect("Expected an error.");
+
+    match error {
+        ExclaimError::Parse(location, msg) => {
+            assert_eq!(&debug_string(&location), &debug_string(&Location::with_offset(0, 11, 11)));
+            assert_eq!(&msg, "Expected a negative number that fits in a signed integer, but found '9223372036854775809'.");
+        },
+        _ => panic!("Expected a parse error, but got: {}", error),
+    }
+}
+
+#[test]
+fn parse_try_run_duplicate_object_key() {
+    let input = r#"{{ write! { name: 1, "name": 2 } }}"#;
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+
+    match error {
+        ExclaimError::Parse(location, msg) => {
+            assert_eq!(&debug_string(&location), &debug_string(&Location::with_offset(0, 21, 21)));
+            assert_eq!(&msg, concat!(
+                "The key 'name' is declared more than once in the object. On line [0; 21]:\n",
+                "\t{{ write! { name: 1, \"name\": 2 } }}\n",
+                "\t                     ^ duplicate key",
+            ));
+        },
+        _ => panic!("Expected a parse error, but got: {}", error),
+    }
+}
+
+#[test]
+fn parse_try_run_nested_too_deep() {
+    // Deeply nested input is an error instead of a stack overflow
+    let parens = format!("{{{{ write! {}1{} }}}}", "(".repeat(2000), ")".repeat(2000));
+    let negations = format!("{{{{ write! {}true }}}}", "!".repeat(10000));
+
+    let expected = [
+        (parens, Location::with_offset(0, 95, 95)),
+        (negations, Location::with_offset(0, 266, 266)),
+    ];
+
+    for (input, expected_location) in &expected {
+        let tokens = exclaim::try_run_lexer(input).unwrap();
+        let error = exclaim::try_run_parser(tokens).err().expect("Expected an error.");
+
+        match error {
+            ExclaimError::Parse(location, msg) => {
+                assert_eq!(&debug_string(&location), &debug_string(expected_location));
+                assert_eq!(&msg, "The expression is nested too deeply, at most 256 levels of nesting are allowed.");
+            },
+            _ => panic!("Expected a parse error, but got: {}", error),
+        }
+    }
+}
+
+#[test]
+fn parse_try_run_collections_nested_too_deep() {
+    let arrays = format!("{{{{ write! {}{} }}}}", "[".repeat(2000), "]".repeat(2000));
+    let tuples = format!("{{{{ write! {}1,{} }}}}", "(".repeat(2000), ")".repeat(2000));
+    let objects = format!("{{{{ write! {}1{} }}}}", "{ a: ".repeat(2000), " }".repeat(2000));
+
+    let expected = [
+        (arrays, Location::with_offset(0, 95, 95)),
+        (tuples, Location::with_offset(0, 95, 95)),
+        (objects, Location::with_offset(0, 435, 435)),
+    ];
+
+    for (input, expected_location) in &expected {
+        let tokens = exclaim::try_run_lexer(input).unwrap();
+        let error = exclaim::try_run_parser(tokens).err().expect("Expected an error.");
+
+        match error {
+            ExclaimError::Parse(location, msg) => {
+                assert_eq!(&debug_string(&location), &debug_string(expected_location));
+                assert_eq!(&msg, "The expression is nested too deeply, at most 256 levels of nesting are allowed.");
+            },
+            _ => panic!("Expected a parse error, but got: {}", error),
+        }
+    }
+}
+
+#[test]
+fn parse_nested_within_limit() {
+    let input = format!("{{{{ write! {}1{} }}}}{{{{ write! {}true }}}}", "(".repeat(80), ")".repeat(80), "!".repeat(250));
+
+    let output = exclaim::try_run(&input, None).unwrap();
+    assert_eq!(&output, "1true");
+
+    let input = format!("{{{{ write! {}1{} | len }}}}{{{{ write! {}1{}{} | unwrap }}}}", "[".repeat(80), "]".repeat(80), "{ a: ".repeat(80), " }".repeat(80), ".a".repeat(80));
+
+    let output = exclaim::try_run(&input, None).unwrap();
+    assert_eq!(&output, "11");
+}
@@ -2,13 +2,16 @@ use std::collections::BTreeMap;
 
 use crate::common::{
     PrettyString, 
-    read_file_to_string
+    read_file_to_string,
+    debug_string,
 };
 
 use exclaim::{
     DataContext,
     Data,
+    ExclaimError,
 };
+use exclaim::common::Location;
 
 // Overrides std lib assert_eq with PrettyString version of assert_eq. 
 // You need to include common::PrettyString newtype
@@ -549,4 +552,533 @@ fn runtime_take_upper_lower() {
     
     let output = exclaim::run(input, None);
     pretty_assertions::assert_eq!(&output, expected)
-}
\ No newline at end of file
+}
+#[test]
+fn runtime_try_run_transform_error() {
+    let input = r#"A int into uint: {{ write! -1234 | uint }}"#;
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+    match error {
+        ExclaimError::Transform(location, msg) => {
+            assert_eq!(&debug_string(&location), &debug_string(&Location::with_offset(0, 35, 35)));
+            assert_eq!(&msg, "Unable to transform a negative integer into an unsigned integer");
+        },
+        _ => panic!("Expected a transform error, but got: {}", error),
+    }
+}
+
+#[test]
+fn runtime_try_run_invalid_member_access() {
+    let input = r#"{{ let! name = "exclaim" }}{{ write! name.length }}"#;
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+    match error {
+        ExclaimError::Runtime(location, _) => assert_eq!(&debug_string(&location), &debug_string(&Location::with_offset(0, 42, 42))),
+        _ => panic!("Expected a runtime error, but got: {}", error),
+    }
+}
+
+#[test]
+fn runtime_try_run_invalid_string_to_uint() {
+    let input = r#"{{ write! "twelve" | uint }}"#;
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+    assert_eq!(error.message(), "Unable to parse \"twelve\" as an unsigned integer.");
+}
+
+#[test]
+fn runtime_try_run_unknown_transform() {
+    let input = r#"{{ write! "text" | nope }}"#;
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+    assert_eq!(
+        &debug_string(&error),
+        &debug_string(&ExclaimError::Transform(Location::with_offset(0, 19, 19), "Transform 'nope' does not exist.".to_string()))
+    );
+}
+
+#[test]
+fn render_trim_markers() {
+    let input = "<ul>\n{{- render! ch : \"ABC\" | chars -}}\n    <li>{{ write! ch }}</li>\n{{- ! }}\n</ul>";
+    let expected = "<ul><li>A</li><li>B</li><li>C</li>\n</ul>";
+
+    let output = exclaim::run(input, None);
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_trim_markers_next_to_negation() {
+    let input = r#"a {{-write! -5-}} b"#;
+    let expected = "a-5b";
+
+    let output = exclaim::run(input, None);
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_trim_markers_whitespace_only_text() {
+    let input = "{{ let! name = \"Exclaim\" -}}\n\n   {{- write! name }} \t\n{{- write! \"!\" }}";
+    let expected = "Exclaim!";
+
+    let output = exclaim::run(input, None);
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_comment() {
+    let input = "{{# The comment is not rendered,\n    not even {{ write! \"this\" }} #}}Hello{{# again #}}!";
+    let expected = "Hello!";
+
+    let output = exclaim::run(input, None);
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_raw_block() {
+    let input = r#"{{ let! name = "Exclaim" }}{{ raw! }}{{ write! name }}{{!}} is rendered as {{ write! name }}"#;
+    let expected = "{{ write! name }} is rendered as Exclaim";
+
+    let output = exclaim::run(input, None);
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_raw_block_trim_markers() {
+    let input = "<p>\n  {{- raw! -}}\n  {{ write! name }}\n  {{- ! -}}\n</p> {{ raw! }}{{ write! name }}{{ ! }}";
+    let expected = "<p>{{ write! name }}</p> {{ write! name }}";
+
+    let output = exclaim::run(input, None);
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_text_with_braces() {
+    let input = r#"<style>p { color: red; }</style>{"a": {"b": 1}} \{{ write! "x" }} {{ write! "y" }}"#;
+    let expected = r#"<style>p { color: red; }</style>{"a": {"b": 1}} {{ write! "x" }} y"#;
+
+    let output = exclaim::run(input, None);
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_labels_with_digits_and_underscores() {
+    let input = r#"{{ let! _id = 7 }}{{ let! address_line1 = "Main St" }}{{ write! _id }} {{ write! address_line1 }}"#;
+    let expected = "7 Main St";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_bool_and_null_literals() {
+    let input = r#"{{ let! ready = true }}{{ write! ready }} {{ write! false }} {{ write! null }}"#;
+    let expected = "true false null";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_bool_and_null_transforms() {
+    let input = r#"{{ write! true | int }} {{ write! false | float }} {{ write! true | string | uppercase }} {{ write! "false" | bool }} {{ write! 0 | bool }} {{ write! null | string | len }}"#;
+    let expected = "1 0 TRUE false false 4";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn data_equality() {
+    assert!(Data::Bool(true) == Data::Bool(true));
+    assert!(Data::Bool(true) != Data::Bool(false));
+    assert!(Data::Null == Data::Null);
+    assert!(Data::Null != Data::Bool(false));
+    assert!(Data::Uint(1) == Data::Int(1));
+    assert!(Data::Int(-1) != Data::Uint(usize::MAX));
+    assert!(Data::Float(2.0) == Data::Uint(2));
+    assert!(Data::String("1".to_string()) != Data::Uint(1));
+    assert!(Data::Array(vec![Data::Int(1), Data::Null]) == Data::Array(vec![Data::Uint(1), Data::Null]));
+}
+
+#[test]
+fn render_if_elif_else_branches() {
+    let input = r#"{{ render! n : "012" | chars }}{{ if! n | int }}{{ write! n }}{{ elif! "" }}empty{{ else! }}zero{{!}} {{!}}"#;
+    let expected = "zero 1 2 ";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_if_nested_and_without_else() {
+    let input = r#"{{ if! true }}a{{ if! false }}b{{ elif! null }}c{{!}}d{{!}}{{ if! 0 }}e{{!}}"#;
+    let expected = "ad";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_if_branch_scope() {
+    let input = r#"{{ let! x = "outer" }}{{ if! x }}{{ let! x = "inner" }}{{ write! x }}{{!}} {{ write! x }}"#;
+    let expected = "inner outer";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_if_global_truthiness() {
+    let input = r#"{{ if! items }}items{{!}} {{ if! missing }}missing{{ else! }}no missing{{!}} {{ if! empty | unwrap }}empty{{ else! }}no empty{{!}}"#;
+    let expected = "items no missing no empty";
+
+    let mut data = DataContext::new();
+    data.insert("items".to_string(), Data::Array(vec![Data::Null]));
+    data.insert("empty".to_string(), Data::Array(vec![]));
+
+    let output = exclaim::try_run(input, Some(data)).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn data_truthiness() {
+    assert!(!Data::String(String::new()).is_truthy());
+    assert!(Data::String("0".to_string()).is_truthy());
+    assert!(!Data::Int(0).is_truthy());
+    assert!(Data::Int(-1).is_truthy());
+    assert!(!Data::Uint(0).is_truthy());
+    assert!(!Data::Float(0.0).is_truthy());
+    assert!(Data::Float(0.5).is_truthy());
+    assert!(!Data::Bool(false).is_truthy());
+    assert!(!Data::Null.is_truthy());
+    assert!(!Data::Tuple(Box::new([])).is_truthy());
+    assert!(!Data::Object(BTreeMap::new()).is_truthy());
+    assert!(!Data::Option(None).is_truthy());
+    assert!(!Data::Option(Some(Box::new(Data::Bool(false)))).is_truthy());
+    assert!(Data::Option(Some(Box::new(Data::Bool(true)))).is_truthy());
+}
+
+#[test]
+fn render_comparison_operators() {
+    let input = r#"{{ write! 1 < 2 }} {{ write! 2 <= 1.5 }} {{ write! -1 > 0 }} {{ write! 2.5 >= 2 }} {{ write! "apple" < "banana" }} {{ write! 1 == 1.0 }} {{ write! "1" != 1 }}"#;
+    let expected = "true false false true true true true";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_boolean_operators_precedence() {
+    let input = r#"{{ write! true || false && false }} {{ write! !true || 1 < 2 == true }} {{ write! !!"text" }} {{ write! ! 0 && null == null }}"#;
+    let expected = "true true true true";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_boolean_operators_short_circuit() {
+    // The right hand side would fail, since there is nothing to unwrap
+    let input = r#"{{ write! false && missing | unwrap }} {{ write! true || missing | unwrap }}"#;
+    let expected = "false true";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_comparison_globals() {
+    let input = r#"{{ if! count > 10 && name == "exclaim" }}yes{{!}} {{ write! missing == null }}"#;
+    let expected = "yes true";
+
+    let mut data = DataContext::new();
+    data.insert("count".to_string(), Data::Uint(144));
+    data.insert("name".to_string(), Data::String("exclaim".to_string()));
+
+    let output = exclaim::try_run(input, Some(data)).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_comparison_type_error() {
+    let input = r#"{{ write! "10" < 2 }}"#;
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+    assert_eq!(
+        &debug_string(&error),
+        &debug_string(&ExclaimError::Runtime(Location::with_offset(0, 15, 15), "Unable to compare \"10\" with 2, '<' can only compare numbers with numbers or strings with strings.".to_string()))
+    );
+}
+
+#[test]
+fn data_compare() {
+    use std::cmp::Ordering;
+
+    assert!(Data::Int(-1).compare(&Data::Uint(usize::MAX)) == Some(Ordering::Less));
+    assert!(Data::Uint(3).compare(&Data::Float(2.5)) == Some(Ordering::Greater));
+    assert!(Data::Float(2.0).compare(&Data::Int(2)) == Some(Ordering::Equal));
+    assert!(Data::String("b".to_string()).compare(&Data::String("a".to_string())) == Some(Ordering::Greater));
+    assert!(Data::Float(f64::NAN).compare(&Data::Int(0)).is_none());
+    assert!(Data::Bool(true).compare(&Data::Bool(false)).is_none());
+}
+
+#[test]
+fn render_arithmetic_operators() {
+    let input = r#"{{ write! 1 + 2 * 3 }} {{ write! (1 + 2) * 3 }} {{ write! 7 / 2 }} {{ write! -7 % 3 }} {{ write! 10 - 4 - 3 }} {{ write! 1.5 * 2 }} {{ write! 7 / 2.0 }}"#;
+    let expected = "7 9 3 -1 3 3 3.5";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_arithmetic_promotion() {
+    let input = r#"{{ write! 1 - 2 }} {{ write! 2 - 3 | float }} {{ write! price * count }} {{ write! (count + 1) | string | len }}"#;
+    let expected = "-1 -1 7.5 1";
+
+    let mut data = DataContext::new();
+    data.insert("price".to_string(), Data::Float(2.5));
+    data.insert("count".to_string(), Data::Uint(3));
+
+    let output = exclaim::try_run(input, Some(data)).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_negation() {
+    let input = r#"{{ let! a = 5 }}{{ write! -a }} {{ write! -(a + 1) }} {{ write! 0 - -a }} {{ write! -a * -2.5 }} {{ write! -items[0] }} {{ write! -1 | string | len }}"#;
+    let expected = "-5 -6 5 12.5 -3 2";
+
+    let mut data = DataContext::new();
+    data.insert("items".to_string(), Data::Array(vec![Data::Uint(3)]));
+
+    let output = exclaim::try_run(input, Some(data)).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_negation_limits() {
+    let input = format!("{{{{ write! {} }}}}", isize::MIN);
+    let output = exclaim::try_run(&input, None).unwrap();
+    assert_eq!(&output, &isize::MIN.to_string());
+
+    let mut data = DataContext::new();
+    data.insert("min".to_string(), Data::Int(isize::MIN));
+
+    let error = exclaim::try_run(r#"{{ write! -min }}"#, Some(data)).unwrap_err();
+    assert_eq!(
+        &debug_string(&error),
+        &debug_string(&ExclaimError::Runtime(Location::with_offset(0, 10, 10), format!("Overflow while negating {}.", isize::MIN)))
+    );
+}
+
+#[test]
+fn render_negation_type_error() {
+    let input = r#"{{ write! -"a" }}"#;
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+    assert_eq!(
+        &debug_string(&error),
+        &debug_string(&ExclaimError::Runtime(Location::with_offset(0, 10, 10), "Unable to compute -\"a\", '-' only works on numbers.".to_string()))
+    );
+}
+
+#[test]
+fn render_string_concatenation() {
+    let input = r#"{{ let! name = "exclaim" }}{{ write! "Hello, " + name + "!" }} {{ write! "v" + 1 + 0.5 }} {{ write! 1 + 2 + "3" }}"#;
+    let expected = "Hello, exclaim! v10.5 33";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_arithmetic_overflow_error() {
+    let input = format!("{{{{ write! {} + 1 }}}}", usize::MAX);
+
+    let error = exclaim::try_run(&input, None).unwrap_err();
+    assert_eq!(
+        &debug_string(&error),
+        &debug_string(&ExclaimError::Runtime(Location::with_offset(0, 31, 31), format!("Overflow while computing {} + 1.", usize::MAX)))
+    );
+}
+
+#[test]
+fn render_division_by_zero_error() {
+    let input = r#"{{ write! 1 % 0 }}"#;
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+    assert_eq!(
+        &debug_string(&error),
+        &debug_string(&ExclaimError::Runtime(Location::with_offset(0, 12, 12), "Division by zero while computing 1 % 0.".to_string()))
+    );
+}
+
+#[test]
+fn render_float_division_by_zero_error() {
+    let input = r#"{{ write! 1.5 / 0 }}"#;
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+    assert_eq!(
+        &debug_string(&error),
+        &debug_string(&ExclaimError::Runtime(Location::with_offset(0, 14, 14), "Division by zero while computing 1.5 / 0.".to_string()))
+    );
+}
+
+#[test]
+fn render_arithmetic_type_error() {
+    let input = r#"{{ write! true * 2 }}"#;
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+    assert_eq!(
+        &debug_string(&error),
+        &debug_string(&ExclaimError::Runtime(Location::with_offset(0, 15, 15), "Unable to compute true * 2, '*' only works on numbers.".to_string()))
+    );
+}
+
+#[test]
+fn render_array_literal() {
+    let input = r#"{{ render! n : [1, 2 + 1, "x",] }}{{ write! n }}{{!}} {{ write! [] | len }} {{ write! [[1, 2], [3]] | get(1) | unwrap }}"#;
+    let expected = "13x 0 [3]";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_tuple_literal() {
+    let input = r#"{{ let! (name, count) = ("apples", 2 * 3) }}{{ write! count }} {{ write! name }} {{ write! ("a",) }} {{ write! () | len }} {{ write! (1) }}"#;
+    let expected = "6 apples (\"a\") 0 1";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_object_literal() {
+    let input = r#"{{ let! page = { title: "Home", "page count": 2, tags: ["a", "b"], } }}{{ write! page.title | unwrap }} {{ write! page | get("page count") | unwrap }} {{ write! page.tags | unwrap | len }} {{ if! {} }}full{{ else! }}empty{{!}}"#;
+    let expected = "Home 2 2 empty";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_collection_literal_transform_arguments() {
+    let input = r#"{{ write! "a" | concat(["b", "c"] | concat) }}"#;
+    let expected = "abc";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_index_access() {
+    let input = r#"{{ write! items[0] }} {{ write! items[-1] }} {{ write! items[i + 1] }}"#;
+    let expected = "a c c";
+
+    let mut data = DataContext::new();
+    data.insert("items".to_string(), Data::Array(vec![
+        Data::String("a".to_string()),
+        Data::String("b".to_string()),
+        Data::String("c".to_string()),
+    ]));
+    data.insert("i".to_string(), Data::Int(1));
+
+    let output = exclaim::try_run(input, Some(data)).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_index_access_local() {
+    let input = r#"{{ let! xs = [1, 2] }}{{ let! obj = { "some key": "value" } }}{{ write! xs[0] }} {{ write! xs[-1] }} {{ write! obj["some key"] }}"#;
+    let expected = "1 2 value";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_key_access() {
+    let input = r#"{{ write! site["page count"] }} {{ write! site.pages[1].title | unwrap }} {{ write! site["pages"][0]["title"] }}"#;
+    let expected = "2 About Home";
+
+    let page = |title: &str| {
+        let mut page = BTreeMap::new();
+        page.insert("title".to_string(), Data::String(title.to_string()));
+        Data::Object(page)
+    };
+
+    let mut site = BTreeMap::new();
+    site.insert("page count".to_string(), Data::Uint(2));
+    site.insert("pages".to_string(), Data::Array(vec![page("Home"), page("About")]));
+
+    let mut data = DataContext::new();
+    data.insert("site".to_string(), Data::Object(site));
+
+    let output = exclaim::try_run(input, Some(data)).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_index_out_of_range() {
+    let input = r#"{{ let! xs = [1, 2] }}{{ write! xs[0] }}{{ write! xs[-3] }}"#;
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+    assert_eq!(
+        &debug_string(&error),
+        &debug_string(&ExclaimError::Runtime(Location::with_offset(0, 53, 53), "Unable to access the element at -3, because the index is out of range.".to_string()))
+    );
+}
+
+#[test]
+fn render_missing_key_access() {
+    let input = r#"{{ let! obj = { "a": 1 } }}{{ write! obj["a"] }}{{ write! obj["b"] }}"#;
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+    assert_eq!(
+        &debug_string(&error),
+        &debug_string(&ExclaimError::Runtime(Location::with_offset(0, 62, 62), "Unable to access the key \"b\", because it doesn't exist.".to_string()))
+    );
+}
+
+#[test]
+fn render_sub_expression_access() {
+    let input = r#"{{ let! pair = ("x", 2) }}{{ write! pair[1] }} {{ write! [1, 2, 3][-1] }} {{ write! { "a b": [4] }["a b"][0] }} {{ write! (pair)[0] | uppercase }}"#;
+    let expected = "2 3 4 X";
+
+    let output = exclaim::try_run(input, None).unwrap();
+    assert_eq!(&output, expected)
+}
+
+#[test]
+fn render_index_type_error() {
+    let input = r#"{{ let! items = [1, 2] }}{{ write! items["first"] }}"#;
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+    assert_eq!(
+        &debug_string(&error),
+        &debug_string(&ExclaimError::Runtime(Location::with_offset(0, 41, 41), "Arrays and tuples can only be indexed with integers, but found \"first\".".to_string()))
+    );
+}
+
+#[test]
+fn render_index_none_error() {
+    let input = r#"{{ write! missing[0] }}"#;
+
+    let error = exclaim::try_run(input, None).unwrap_err();
+    assert_eq!(
+        &debug_string(&error),
+        &debug_string(&ExclaimError::Runtime(Location::with_offset(0, 18, 18), "Can't index the option with 0, because the option is none.".to_string()))
+    );
+}
+
+#[test]
+fn data_index() {
+    let array = Data::Array(vec![Data::Uint(1), Data::Uint(2)]);
+    assert!(array.index(&Data::Int(-2)) == Ok(Some(&Data::Uint(1))));
+    assert!(array.index(&Data::Uint(2)) == Ok(None));
+    assert!(array.index(&Data::Int(isize::MIN)) == Ok(None));
+    assert!(array.index(&Data::Option(Some(Box::new(Data::Uint(1))))) == Ok(Some(&Data::Uint(2))));
+    assert!(Data::Uint(1).index(&Data::Uint(0)).is_err());
+}
@@ -89,4 +89,93 @@ End File Scope
     let ast = exclaim::run_semantics(ast);
 
     assert_eq!(&Serializer::serialize(&ast), &expected);
-}
\ No newline at end of file
+}
+#[test]
+fn unclosed_block_error_location() {
+    let input = r#"
+{{ render! a : b }}
+    {{ write! a }}
+"#;
+
+    let tokens = exclaim::try_run_lexer(input).unwrap();
+    let ast = exclaim::try_run_parser(tokens).unwrap();
+    let error = match exclaim::try_run_semantics(ast) {
+        Ok(_) => panic!("Expected an error."),
+        Err(e) => e,
+    };
+
+    match error {
+        exclaim::ExclaimError::Semantic(location, _) => pretty_assertions::assert_eq!(location, exclaim::common::Location::with_offset(1, 3, 4)),
+        _ => panic!("Expected a semantic error, but got: {}", error),
+    }
+}
+
+#[test]
+#[should_panic(expected = "The block! region 'content' is declared more than once.")]
+fn duplicate_block_region() {
+    let input = r#"{{ block! content }}a{{!}}{{ block! content }}b{{!}}"#;
+
+    let tokens = exclaim::run_lexer(input);
+    let ast = exclaim::run_parser(tokens);
+    let _ast = exclaim::run_semantics(ast);
+}
+
+#[test]
+#[should_panic(expected = "super! can only be used inside of a block! region.")]
+fn super_outside_block_region() {
+    let input = r#"{{ super! }}"#;
+
+    let tokens = exclaim::run_lexer(input);
+    let ast = exclaim::run_parser(tokens);
+    let _ast = exclaim::run_semantics(ast);
+}
+
+#[test]
+#[should_panic(expected = "extends! can only be used in the file scope.")]
+fn extends_outside_file_scope() {
+    let input = r#"{{ block! content }}{{ extends! "base" }}{{!}}"#;
+
+    let tokens = exclaim::run_lexer(input);
+    let ast = exclaim::run_parser(tokens);
+    let _ast = exclaim::run_semantics(ast);
+}
+
+#[test]
+#[should_panic(expected = "elif! can only follow an if! or elif! branch.")]
+fn elif_outside_if() {
+    let input = r#"{{ render! a : b }}{{ elif! a }}{{!}}{{!}}"#;
+
+    let tokens = exclaim::run_lexer(input);
+    let ast = exclaim::run_parser(tokens);
+    let _ast = exclaim::run_semantics(ast);
+}
+
+#[test]
+#[should_panic(expected = "else! can only follow an if! or elif! branch.")]
+fn else_in_file_scope() {
+    let input = r#"{{ else! }}{{!}}"#;
+
+    let tokens = exclaim::run_lexer(input);
+    let ast = exclaim::run_parser(tokens);
+    let _ast = exclaim::run_semantics(ast);
+}
+
+#[test]
+#[should_panic(expected = "elif! can't follow an else! branch.")]
+fn elif_after_else() {
+    let input = r#"{{ if! a }}{{ else! }}{{ elif! b }}{{!}}"#;
+
+    let tokens = exclaim::run_lexer(input);
+    let ast = exclaim::run_parser(tokens);
+    let _ast = exclaim::run_semantics(ast);
+}
+
+#[test]
+#[should_panic(expected = "Expected the scope to be closed with {{!}}")]
+fn unclosed_if() {
+    let input = r#"{{ if! a }}a{{ else! }}b"#;
+
+    let tokens = exclaim::run_lexer(input);
+    let ast = exclaim::run_parser(tokens);
+    let _ast = exclaim::run_semantics(ast);
+}
@@ -0,0 +1,268 @@
+use std::io;
+
+use exclaim::{
+    CompileOptions,
+    Data,
+    DataContext,
+    ExclaimError,
+    Template,
+};
+use exclaim::common::Location;
+
+/// A writer that fails after accepting a set number of bytes
+struct FailingWriter {
+    capacity: usize,
+}
+
+impl io::Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > self.capacity {
+            return Err(io::Error::other("sink is full"));
+        }
+
+        self.capacity -= buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn template_render_many() {
+    let input = r#"Hello, {{ write! name | unwrap }}!"#;
+    let template = Template::compile(input).unwrap();
+
+    for name in ["Earth", "Mars", "Venus"].iter() {
+        let mut data = DataContext::new();
+        data.insert("name".to_string(), Data::String(name.to_string()));
+
+        let output = template.render(&data).unwrap();
+        pretty_assertions::assert_eq!(output, format!("Hello, {}!", name));
+    }
+}
+
+#[test]
+fn template_render_scoped_blocks_many() {
+    let input = r#"{{ let! greeting = "Hi " }}{{ render! ch : letters | unwrap | chars }}{{ write! greeting }}{{ write! ch }} {{!}}"#;
+    let template = Template::compile(input).unwrap();
+
+    let mut data = DataContext::new();
+    data.insert("letters".to_string(), Data::String("AB".to_string()));
+    let output = template.render(&data).unwrap();
+    pretty_assertions::assert_eq!(output, "Hi A Hi B ");
+
+    let mut data = DataContext::new();
+    data.insert("letters".to_string(), Data::String("XYZ".to_string()));
+    let output = template.render(&data).unwrap();
+    pretty_assertions::assert_eq!(output, "Hi X Hi Y Hi Z ");
+}
+
+#[test]
+fn template_compile_error() {
+    let input = r#"{{ write! "unclosed" "#;
+
+    match Template::compile(input) {
+        Ok(_) => panic!("Expected the template to fail to compile."),
+        Err(e) => pretty_assertions::assert_eq!(e.stage(), "Parser"),
+    }
+}
+
+#[test]
+fn template_compile_error_snippet() {
+    let input = r#"{{ let! title "Exclaim" }}"#;
+
+    match Template::compile(input) {
+        Ok(_) => panic!("Expected the template to fail to compile."),
+        Err(e) => {
+            pretty_assertions::assert_eq!(e.location(), exclaim::common::Location::with_offset(0, 14, 14));
+            assert!(e.message().contains("but found '\"Exclaim\"'"));
+            assert!(e.message().ends_with("\t              ^ expected '=' to assign an expression in the let! statement"));
+        },
+    }
+}
+
+#[test]
+fn template_render_to_fmt_sink() {
+    let input = r#"{{ render! ch : "ABC" | chars }}<{{ write! ch }}>{{!}}"#;
+    let template = Template::compile(input).unwrap();
+
+    let mut output = String::from("Letters: ");
+    template.render_to(&DataContext::new(), &mut output).unwrap();
+    pretty_assertions::assert_eq!(output, "Letters: <A><B><C>");
+}
+
+#[test]
+fn template_write_to_io_sink() {
+    let input = r#"{{ render! ch : "ABC" | chars }}<{{ write! ch }}>{{!}}"#;
+    let template = Template::compile(input).unwrap();
+
+    let mut output: Vec<u8> = vec![];
+    template.write_to(&DataContext::new(), &mut output).unwrap();
+    pretty_assertions::assert_eq!(String::from_utf8(output).unwrap(), "<A><B><C>");
+}
+
+#[test]
+fn template_write_to_io_error() {
+    let input = r#"Hello, {{ write! "world" }}!"#;
+    let template = Template::compile(input).unwrap();
+
+    let mut writer = FailingWriter { capacity: 8 };
+    let error = template.write_to(&DataContext::new(), &mut writer).unwrap_err();
+    pretty_assertions::assert_eq!(
+        error, 
+        ExclaimError::Runtime(Location::with_offset(0, 10, 10), "Unable to write the rendered output: sink is full".to_string())
+    );
+}
+
+#[test]
+fn template_standalone_lines() {
+    let input = "{{ render! name : names | unwrap }}\n{{ write! name }}\n{{!}}";
+    let options = CompileOptions::new().standalone_lines(true);
+    let template = Template::compile_with_options(input, &options).unwrap();
+
+    let mut data = DataContext::new();
+    data.insert("names".to_string(), Data::Array(vec![
+        Data::String("test".to_string()),
+        Data::String("apple".to_string()),
+    ]));
+
+    let output = template.render(&data).unwrap();
+    pretty_assertions::assert_eq!(output, "test\napple\n");
+}
+
+#[test]
+fn template_standalone_lines_indented() {
+    let input = "<ul>\n    {{ let! items = \"AB\" | chars }}\n    {{ render! item : items }}\n    <li>{{ write! item }}</li>\n    {{!}}\n</ul>\n";
+    let options = CompileOptions::new().standalone_lines(true);
+    let template = Template::compile_with_options(input, &options).unwrap();
+
+    let output = template.render(&DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "<ul>\n    <li>A</li>\n    <li>B</li>\n</ul>\n");
+}
+
+#[test]
+fn template_standalone_lines_only_block_only_lines() {
+    // Blocks sharing their line with text or a write! block are left untouched 
+    let input = "a {{ let! x = 1 }}\n{{ write! x }}{{ let! y = 2 }}\n{{ let! z = 3 }} b\n{{ write! y }}";
+    let options = CompileOptions::new().standalone_lines(true);
+    let template = Template::compile_with_options(input, &options).unwrap();
+
+    let output = template.render(&DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "a \n1\n b\n2");
+}
+
+#[test]
+fn template_standalone_lines_if_else() {
+    let input = "{{ if! ready }}\n  ready\n{{ else! }}\n  waiting\n{{!}}\ndone";
+    let options = CompileOptions::new().standalone_lines(true);
+    let template = Template::compile_with_options(input, &options).unwrap();
+
+    let output = template.render(&DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "  waiting\ndone");
+}
+
+#[test]
+fn template_standalone_lines_regions_comments_and_raw() {
+    let input = "a\n  {{ block! x }}\nb\n  {{!}}\n{{# note #}}\n{{ raw! }}\n{{ write! c }}\n{{!}}\nc";
+    let options = CompileOptions::new().standalone_lines(true);
+    let template = Template::compile_with_options(input, &options).unwrap();
+
+    let output = template.render(&DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "a\nb\n{{ write! c }}\nc");
+}
+
+#[test]
+fn template_standalone_lines_disabled_by_default() {
+    let input = "{{ let! x = 1 }}\n{{ write! x }}";
+    let template = Template::compile(input).unwrap();
+
+    let output = template.render(&DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "\n1");
+}
+
+#[test]
+fn template_custom_delimiters() {
+    let input = "<div>{{ message }}</div>\n<%# comment #%><% let! name = \"Exclaim\" -%>\n<p><% write! name %></p> <% raw! %><% write! name %><%!%>";
+    let options = CompileOptions::new().delimiters("<%", "%>");
+    let template = Template::compile_with_options(input, &options).unwrap();
+
+    let output = template.render(&DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "<div>{{ message }}</div>\n<p>Exclaim</p> <% write! name %>");
+}
+
+#[test]
+fn template_custom_delimiters_brackets() {
+    let input = r#"[[ render! ch : "AB" | chars ]]<li>[[ write! ch ]]</li>[[!]]"#;
+    let options = CompileOptions::new().delimiters("[[", "]]");
+    let template = Template::compile_with_options(input, &options).unwrap();
+
+    let output = template.render(&DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "<li>A</li><li>B</li>");
+}
+
+#[test]
+fn template_invalid_delimiters() {
+    let options = CompileOptions::new().delimiters("", "}}");
+
+    match Template::compile_with_options("text", &options) {
+        Ok(_) => panic!("Expected the template to fail to compile."),
+        Err(e) => {
+            pretty_assertions::assert_eq!(e.stage(), "Lexer");
+            pretty_assertions::assert_eq!(e.message(), "Lexer: Invalid delimiter \"\". Delimiters can't be empty or contain whitespace.");
+        },
+    }
+}
+
+#[test]
+fn template_crlf_standalone_lines_normalized() {
+    let input = "\u{FEFF}<ul>\r\n{{ render! ch : \"AB\" | chars }}\r\n  <li>{{ write! ch }}</li>\r\n{{!}}\r\n</ul>";
+    let options = CompileOptions::new().standalone_lines(true).normalize_line_endings(true);
+    let template = Template::compile_with_options(input, &options).unwrap();
+
+    let output = template.render(&DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "<ul>\n  <li>A</li>\n  <li>B</li>\n</ul>");
+}
+
+#[test]
+fn template_crlf_standalone_lines_preserved() {
+    let input = "\u{FEFF}<ul>\r\n{{ render! ch : \"AB\" | chars }}\r\n  <li>{{ write! ch }}</li>\r\n{{!}}\r\n</ul>";
+    let options = CompileOptions::new().standalone_lines(true);
+    let template = Template::compile_with_options(input, &options).unwrap();
+
+    let output = template.render(&DataContext::new()).unwrap();
+    pretty_assertions::assert_eq!(output, "<ul>\r\n  <li>A</li>\r\n  <li>B</li>\r\n</ul>");
+}
+
+#[test]
+fn template_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Template>();
+
+    // A compiled template can be rendered from several threads at once
+    let template = std::sync::Arc::new(Template::compile(r#"{{ render! ch : "AB" | chars }}{{ write! ch }}{{!}}"#).unwrap());
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let template = std::sync::Arc::clone(&template);
+            std::thread::spawn(move || template.render(&DataContext::new()).unwrap())
+        })
+        .collect();
+
+    for handle in handles {
+        pretty_assertions::assert_eq!(handle.join().unwrap(), "AB");
+    }
+}
+
+#[test]
+fn template_outlives_input() {
+    let template = {
+        let input = String::from(r#"{{ let! greeting = "Hello" }}{{ write! greeting }}, {{ write! "\"world\"" }}!"#);
+        Template::compile(&input).unwrap()
+    };
+
+    // The template keeps its own copy of the input, so moving it keeps the text its tokens borrow
+    let template = Box::new(template);
+    pretty_assertions::assert_eq!(template.source(), r#"{{ let! greeting = "Hello" }}{{ write! greeting }}, {{ write! "\"world\"" }}!"#);
+    pretty_assertions::assert_eq!(template.render(&DataContext::new()).unwrap(), "Hello, \"world\"!");
+}
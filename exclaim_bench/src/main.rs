@@ -1 +1,99 @@
-fn main() {}
\ No newline at end of file
+use std::alloc::{
+    GlobalAlloc,
+    Layout,
+    System,
+};
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
+use std::time::{
+    Duration,
+    Instant,
+};
+
+/// Tracks the heap memory in use and the number of allocations, so the peak memory of a run can be reported 
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::Relaxed);
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ITERATIONS: u32 = 10;
+
+fn main() {
+    let long_text = include_str!("../data/long_text.txt");
+
+    // Plain text, a single huge text token 
+    let text: String = long_text.repeat(32);
+    bench("lexer: plain text", &text, exclaim::run_lexer);
+    bench("compile: plain text", &text, compile);
+
+    // Text mixed with code blocks 
+    let mut blocks = String::new();
+    for (index, line) in long_text.lines().cycle().take(20_000).enumerate() {
+        blocks.push_str(&format!("{}{{{{ let! item{} = \"value {}\" }}}}{{{{ write! item{} | uppercase }}}}\n", line, index, index, index));
+    }
+    bench("lexer: text and blocks", &blocks, exclaim::run_lexer);
+    bench("compile: text and blocks", &blocks, compile);
+
+    // String literals with escape sequences, which have to be copied 
+    let escapes = "{{ write! \"tab\\tnew line\\nquote\\\"\" }}".repeat(50_000);
+    bench("lexer: escaped strings", &escapes, exclaim::run_lexer);
+    bench("compile: escaped strings", &escapes, compile);
+}
+
+/// The whole compilation of a template: lexer, parser and semantic analysis
+fn compile(input: &str) -> exclaim::Template {
+    exclaim::Template::compile(input).unwrap()
+}
+
+/// Runs the stage on the input, the output is kept until the peak memory has been read
+fn bench<'a, T>(name: &str, input: &'a str, run: impl Fn(&'a str) -> T) {
+    let mut total = Duration::default();
+    let mut peak = 0;
+    let mut allocations = 0;
+
+    for _ in 0..ITERATIONS {
+        let baseline = ALLOCATED.load(Ordering::Relaxed);
+        PEAK.store(baseline, Ordering::Relaxed);
+        ALLOCATIONS.store(0, Ordering::Relaxed);
+
+        let start = Instant::now();
+        let output = run(input);
+        total += start.elapsed();
+
+        peak = peak.max(PEAK.load(Ordering::Relaxed) - baseline);
+        allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        drop(output);
+    }
+
+    println!(
+        "{:<26} input: {:>8} KiB  avg time: {:>10.3?}  peak memory: {:>8} KiB  allocations: {:>8}",
+        name, 
+        input.len() / 1024, 
+        total / ITERATIONS, 
+        peak / 1024,
+        allocations,
+    );
+}